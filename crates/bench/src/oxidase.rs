@@ -1,4 +1,4 @@
-use oxidase::SourceType;
+use oxidase::{SourceType, TranspileOptions};
 
 use crate::Benchee;
 
@@ -11,7 +11,12 @@ impl Benchee for Oxidase {
     const NAME: &str = "oxidase";
     fn run(&mut self, source: &mut String) {
         self.allocator.reset();
-        let ret = oxidase::transpile(&self.allocator, SourceType::ts(), source);
+        let ret = oxidase::transpile(
+            &self.allocator,
+            SourceType::ts(),
+            source,
+            &TranspileOptions::default(),
+        );
         assert!(ret.parser_errors.is_empty());
     }
 }
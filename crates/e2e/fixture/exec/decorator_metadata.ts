@@ -0,0 +1,59 @@
+// @experimentalDecorators: true
+// @emitDecoratorMetadata: true
+
+const metadata: Record<string, unknown>[] = [];
+
+(globalThis as any).Reflect.metadata = (key: string, value: unknown) => {
+    return (target: any, member?: string) => {
+        const owner = typeof target === "function" ? target.name : target.constructor.name;
+        metadata.push({ owner, member: member ?? null, key, value: describe(value) });
+    };
+};
+
+function describe(value: unknown): unknown {
+    if (Array.isArray(value)) {
+        return value.map(describe);
+    }
+    if (typeof value === "function") {
+        return value.name;
+    }
+    return value === undefined ? "undefined" : value;
+}
+
+function noop(...args: any[]) {}
+
+class Dependency {}
+
+interface Shape {}
+
+enum Kind { A }
+
+@noop
+class Service {
+    @noop name: string = "";
+    @noop count: number = 0;
+    @noop flags: boolean[] = [];
+    @noop shape: Shape | undefined;
+    @noop dependency: Dependency | null = null;
+    @noop kind: Kind = Kind.A;
+
+    constructor(dependency: Dependency, label: string, @noop callback: () => void) {}
+
+    @noop
+    method(a: number, b: Dependency): string {
+        return "";
+    }
+
+    @noop
+    async asyncMethod() {}
+
+    @noop
+    set value(value: number) {}
+
+    // Decorators of an accessor pair go on the first one
+    get value(): number {
+        return 0;
+    }
+}
+
+export { metadata };
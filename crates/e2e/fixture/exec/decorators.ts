@@ -0,0 +1,58 @@
+// @experimentalDecorators: true
+
+// Mentions of __decorate and __param in comments and strings don't declare the helpers
+export const helperNames = ["__decorate", "__param"];
+
+export const log: string[] = [];
+
+function logged(name: string) {
+    log.push(`evaluate ${name}`);
+    return (target: any, key?: string | symbol, descriptorOrIndex?: any) => {
+        const kind = typeof target === "function" ? "static" : "instance";
+        log.push(`apply ${name} to ${kind} ${String(key)} ${typeof descriptorOrIndex}`);
+    };
+}
+
+function sealed(constructor: Function) {
+    log.push(`apply sealed to ${constructor.name}`);
+    return class extends (constructor as any) {
+        sealed = true;
+    } as any;
+}
+
+function readonly(target: any, key: string, descriptor: PropertyDescriptor) {
+    descriptor.writable = false;
+    return descriptor;
+}
+
+@sealed
+@logged("class")
+class Decorated {
+    @logged("instanceField") instanceField = 1;
+    @logged("staticField") static staticField = 2;
+
+    @logged("method") @readonly
+    method(@logged("param") param: number) {
+        return param;
+    }
+
+    @logged("staticMethod")
+    static staticMethod() {}
+
+    @logged("getter")
+    get getter() {
+        return 3;
+    }
+
+    constructor(@logged("constructorParam") public value: number) {}
+}
+
+const instance = new Decorated(4);
+
+export const result = {
+    sealed: (instance as any).sealed,
+    value: instance.value,
+    // `sealed` replaces the class with a subclass
+    methodWritable: Object.getOwnPropertyDescriptor(Object.getPrototypeOf(Decorated.prototype), "method")!.writable,
+    staticField: Decorated.staticField,
+};
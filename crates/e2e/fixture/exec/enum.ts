@@ -54,3 +54,108 @@ export declare const enum ExportDeclareConst {
 export const enum ExportConst {
     A
 }
+
+export enum Strings {
+    A = "a",
+    B = `b`,
+    C = A + B,
+    "D\n" = "d",
+}
+
+export enum Mixed {
+    A = 1 << 2,
+    B = -A,
+    C = "c",
+    D = Mixed.A | 1,
+    E = Mixed["B"] * 2,
+    F,
+}
+
+const getValue = (value: any) => value;
+
+export enum NonConstant {
+    A = getValue(1),
+    B = getValue("b"),
+    "C\n" = getValue(2),
+    "D\n" = getValue("d"),
+}
+
+const enum Inlined {
+    A = 1,
+    B = -A,
+    C = "c\n",
+    "D-1" = 4,
+}
+
+export const inlinedValues = [
+    Inlined.A,
+    Inlined.B ** 2,
+    Inlined["C"],
+    Inlined["D-1"],
+    Inlined.A.toString(),
+];
+
+const enum Filled {
+    A = 12345678,
+    Longer = 1234567890123,
+}
+
+export const filledValues = [
+    Filled.A.toString(),
+    Filled.Longer.toFixed(1),
+];
+
+function shadowedByParam(Inlined: { A: string }) {
+    return Inlined.A;
+}
+
+export const shadowedValues = [
+    shadowedByParam({ A: "param" }),
+    (() => {
+        let Inlined = { A: "local" };
+        return Inlined.A;
+    })(),
+];
+
+export enum SameLine { A = 1, B = A + 2, C = SameLine.B, D, E = "e", F = B }
+
+export enum ThisInInitializer {
+    A = typeof this,
+}
+
+export enum SelfNamed {
+    SelfNamed = 1,
+    B,
+    C = B + 1,
+}
+
+export const blockScoped = (() => {
+    const kinds: object[] = [];
+    for (const value of [1, 2]) {
+        enum Kind { A = value }
+        namespace Kind { export const doubled = Kind.A * 2 }
+        kinds.push(Kind);
+    }
+    switch (kinds.length) {
+        case 2:
+            enum Kind { B = 3 }
+            kinds.push(Kind);
+    }
+    return [kinds, kinds[0] !== kinds[1]];
+})();
+
+export declare enum DeclaredThenDefined {
+    A,
+}
+
+export enum DeclaredThenDefined {
+    B = 1,
+}
+
+declare enum DeclaredThenNamespace {
+    A,
+}
+
+export namespace DeclaredThenNamespace {
+    export const a = 1;
+}
@@ -0,0 +1,42 @@
+// @eraseConstEnums: true
+
+const enum Direction {
+    Up = 1,
+    Down,
+    Left = Up << 2,
+    Right = -Left,
+}
+
+const enum Labels {
+    A = "a",
+    B = A + "b",
+}
+
+export const values = [
+    Direction.Up,
+    Direction.Down,
+    Direction["Left"],
+    Direction.Right ** 2,
+    Labels.A,
+    Labels["B"],
+];
+
+export declare const enum Declared {
+    A = 1,
+}
+
+export const shadowed = ((Direction: { Up: string }) => Direction.Up)({ Up: "shadowed" });
+
+export enum NotConst {
+    A = Direction.Down + 1,
+}
+
+const enum Merged {
+    A = 1,
+}
+
+// Nothing declares `Merged` before the namespace
+export let merged = 0;
+namespace Merged {
+    merged = Merged.A;
+}
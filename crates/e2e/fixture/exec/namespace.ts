@@ -71,3 +71,84 @@ export namespace Empty { }
 export namespace Ambient {
     export interface A {}
 }
+
+export namespace SameLine { export const a = 1; export function f() { return a } export const b = f() + a }
+
+export namespace ThisInBody {
+    export const thisType = typeof this;
+}
+
+export namespace ShadowedLater {
+    export const a = 1;
+    export function ShadowedLater() { return a }
+}
+
+export namespace Mutable {
+    export let counter = 0;
+    export var total = 1, doubled = total * 2;
+    export function increment() {
+        counter++;
+        total += counter;
+        return { counter };
+    }
+    increment();
+    ({ total } = { total: total * 10 });
+    export const snapshot = [counter, total, doubled];
+}
+Mutable.increment();
+
+export namespace Merged {
+    export const a = 1;
+    export let b = 2;
+}
+export namespace Merged {
+    export const sum = () => a + b;
+    b = 3;
+}
+
+export class MergedClass {
+    static create() { return new MergedClass(MergedClass.defaultName) }
+    constructor(public name: string) {}
+}
+export namespace MergedClass {
+    export const defaultName = "default";
+}
+
+export function mergedFunction() { return mergedFunction.suffix }
+export namespace mergedFunction {
+    export const suffix = "!";
+}
+
+export enum MergedEnum { A = 1 }
+export namespace MergedEnum {
+    export function double(value: MergedEnum) { return value * 2 }
+}
+
+export namespace Shapes {
+    export const unit = 1;
+    export interface Point { x: number }
+}
+export namespace TypesOnly {
+    export interface Size { width: number }
+}
+export namespace Aliases {
+    export import unit = Shapes.unit;
+    export import Point = Shapes.Point;
+    export import Size = TypesOnly.Size;
+    export const doubled = unit * 2;
+}
+
+export namespace VarInBlock {
+    export let x = 1;
+    function f() {
+        {
+            var x = 2;
+        }
+        for (var y = 0; y < 1; y++) {
+            var z = x + y;
+        }
+        return [x, z];
+    }
+    export const fromFunction = f();
+    export const fromNamespace = x;
+}
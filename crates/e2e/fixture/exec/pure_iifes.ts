@@ -0,0 +1,41 @@
+// @pureIifes: true
+
+export enum Basic {
+    A,
+    B = "b",
+    C = A + 2,
+}
+
+export enum Merged {
+    A = 1,
+}
+
+export enum Merged {
+    B = A + 1,
+}
+
+export namespace N {
+    export const a = 1;
+    export function f() {
+        return a + 1;
+    }
+    export enum E {
+        X = 3,
+    }
+}
+
+export namespace N {
+    export const b = f();
+}
+
+export namespace Outer.Inner {
+    export const c = "c";
+}
+
+export const nested = (() => {
+    {
+        enum Block { A = 4 }
+        namespace Block { export const doubled = Block.A * 2 }
+        return Block;
+    }
+})();
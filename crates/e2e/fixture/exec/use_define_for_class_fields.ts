@@ -0,0 +1,43 @@
+// @useDefineForClassFields: false
+
+const log: string[] = [];
+
+class Base {
+    inherited = "base";
+    set assigned(value: number) {
+        log.push(`set assigned ${value}`);
+    }
+    static set staticAssigned(value: number) {
+        log.push(`set staticAssigned ${value}`);
+    }
+}
+
+class Derived extends Base {
+    // Erased without initializers, keeping the inherited value
+    inherited: string;
+    assigned: number = 1;
+    own = this.assigned;
+    static staticAssigned: number = 2;
+    static staticOwn = `static ${this.name}`;
+    #secret = 3;
+    constructor(public param: number) {
+        super();
+        log.push(`constructor ${this.#secret}`);
+    }
+}
+
+const derived = new Derived(4);
+
+export const instance = {
+    inherited: derived.inherited,
+    own: derived.own,
+    param: derived.param,
+    keys: Object.keys(derived),
+};
+
+export const statics = {
+    staticOwn: Derived.staticOwn,
+    keys: Object.getOwnPropertyNames(Derived).filter((key) => key.startsWith("static")),
+};
+
+export { log };
@@ -0,0 +1,93 @@
+use oxidase::TranspileOptions;
+
+/// Options of an exec fixture, declared in its leading comments like TypeScript test cases:
+///
+/// ```ts
+/// // @experimentalDecorators: true
+/// // @pureIifes: true
+/// ```
+///
+/// Options shared with tsc are passed to it as compiler options. Others only apply to oxidase.
+pub struct FixtureOptions {
+    pub transpile_options: TranspileOptions<'static>,
+    /// JSON object of tsc compiler options, merged into the default ones
+    pub tsc_compiler_options: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FixtureOptionsError {
+    #[error("unknown fixture option `{0}`")]
+    UnknownOption(String),
+    #[error("invalid value `{value}` of fixture option `{name}`, expected `true` or `false`")]
+    InvalidValue { name: String, value: String },
+}
+
+impl FixtureOptions {
+    pub fn parse(source: &str) -> Result<Self, FixtureOptionsError> {
+        let mut transpile_options = TranspileOptions::default();
+        let mut tsc_compiler_options = serde_json::Map::new();
+        for line in source.lines() {
+            let Some(option) = line.trim().strip_prefix("// @") else {
+                break;
+            };
+            let (name, value) = option.split_once(':').unwrap_or((option, "true"));
+            let (name, value) = (name.trim(), value.trim());
+            let value = match value {
+                "true" => true,
+                "false" => false,
+                _ => {
+                    return Err(FixtureOptionsError::InvalidValue {
+                        name: name.to_owned(),
+                        value: value.to_owned(),
+                    })
+                }
+            };
+            let (transpile_option, is_tsc_option) = match name {
+                "experimentalDecorators" => (&mut transpile_options.experimental_decorators, true),
+                "emitDecoratorMetadata" => (&mut transpile_options.emit_decorator_metadata, true),
+                "useDefineForClassFields" => {
+                    (&mut transpile_options.use_define_for_class_fields, true)
+                }
+                "pureIifes" => (&mut transpile_options.pure_iifes, false),
+                "eraseConstEnums" => (&mut transpile_options.erase_const_enums, false),
+                _ => return Err(FixtureOptionsError::UnknownOption(name.to_owned())),
+            };
+            *transpile_option = value;
+            if is_tsc_option {
+                tsc_compiler_options.insert(name.to_owned(), value.into());
+            }
+        }
+        Ok(Self {
+            transpile_options,
+            tsc_compiler_options: serde_json::Value::Object(tsc_compiler_options).to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let options = FixtureOptions::parse(
+            "// @experimentalDecorators: true\n// @pureIifes: true\nexport const a = 1;\n// @eraseConstEnums: true",
+        )
+        .unwrap();
+        assert!(options.transpile_options.experimental_decorators);
+        assert!(options.transpile_options.pure_iifes);
+        assert!(!options.transpile_options.erase_const_enums);
+        assert_eq!(
+            options.tsc_compiler_options,
+            r#"{"experimentalDecorators":true}"#
+        );
+    }
+
+    #[test]
+    fn unknown_option() {
+        assert!(matches!(
+            FixtureOptions::parse("// @strict: true"),
+            Err(FixtureOptionsError::UnknownOption(_))
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 mod cache;
 mod exec;
+mod fixture_options;
 mod format;
 
 use std::{
@@ -20,6 +21,7 @@ use std::{
 use cache::BaselineCache;
 use derive_more::From;
 use exec::{eval, EvalError};
+use fixture_options::{FixtureOptions, FixtureOptionsError};
 use format::format_js;
 use ignore::{DirEntry, WalkBuilder};
 use oxidase::{
@@ -39,10 +41,12 @@ pub struct Failure {
 #[derive(From)]
 pub enum FailureKind {
     IoError(io::Error),
+    InvalidFixtureOptions(FixtureOptionsError),
     OutputInvalidSyntax(String),
     TscInvalidSyntax,
     FormatTscOutputError(anyhow::Error),
     ParserPanicked(Vec<OxcDiagnostic>),
+    UnexpectedDiagnostics(Vec<OxcDiagnostic>),
     Panicked(Box<dyn Any + Send>),
     InputInvalidSyntax(String),
     UnmatchedOutput {
@@ -118,6 +122,10 @@ impl Display for Failure {
                 f.write_str("ExecOutputNotEqual\n")?;
                 pretty_assertions::Comparison::new(expected, actual).fmt(f)?;
             }
+            FailureKind::InvalidFixtureOptions(error) => {
+                f.write_str("invalid_fixture_options\n\n")?;
+                Display::fmt(error, f)?;
+            }
             FailureKind::IoError(error) => {
                 f.write_str("io error\n")?;
                 Debug::fmt(error, f)?;
@@ -134,6 +142,10 @@ impl Display for Failure {
                 f.write_str("ParserPanicked\n")?;
                 Debug::fmt(&diagnostics, f)?;
             }
+            FailureKind::UnexpectedDiagnostics(diagnostics) => {
+                f.write_str("UnexpectedDiagnostics\n")?;
+                Debug::fmt(&diagnostics, f)?;
+            }
         }
         f.write_str("\n")?;
         Ok(())
@@ -284,15 +296,32 @@ fn main() {
                     TestType::Transpile
                 };
 
+                let fixture_options = match test_type {
+                    TestType::Exec => match FixtureOptions::parse(&source) {
+                        Ok(ok) => Some(ok),
+                        Err(error) => {
+                            return Err(Failure {
+                                path: path.to_owned(),
+                                input: source.clone(),
+                                kind: FailureKind::InvalidFixtureOptions(error),
+                            })
+                        }
+                    },
+                    TestType::Transpile => None,
+                };
+
                 let tsc = tsc.get_or_insert_with(Tsc::new);
 
-                let Some(tsc_output) = (match test_type {
-                    TestType::Exec => tsc.process_ts(&source, false, false),
-                    TestType::Transpile => {
-                        baseline_cache.get_or_insert_with(path, file_entry.mtime, || {
-                            tsc.process_ts(&source, true, false)
-                        })
-                    }
+                let Some(tsc_output) = (match &fixture_options {
+                    Some(fixture_options) => tsc.process_ts_with_compiler_options(
+                        &source,
+                        false,
+                        false,
+                        &fixture_options.tsc_compiler_options,
+                    ),
+                    None => baseline_cache.get_or_insert_with(path, file_entry.mtime, || {
+                        tsc.process_ts(&source, true, false)
+                    }),
                 }) else {
                     return if allows_invalid_js() {
                         Ok(())
@@ -317,6 +346,10 @@ fn main() {
                     _ => source,
                 };
 
+                let transpile_options = fixture_options
+                    .map(|fixture_options| fixture_options.transpile_options)
+                    .unwrap_or_default();
+
                 ALLOCATOR
                     .with_borrow_mut(|allocator| -> Result<(), FailureKind> {
                         let allocator = allocator.get_or_insert_with(Allocator::default);
@@ -324,7 +357,12 @@ fn main() {
 
                         let mut output = input.clone();
                         let transpile_return = match catch_unwind(AssertUnwindSafe(|| {
-                            oxidase::transpile(allocator, source_type, &mut output)
+                            oxidase::transpile(
+                                allocator,
+                                source_type,
+                                &mut output,
+                                &transpile_options,
+                            )
                         })) {
                             Ok(ok) => ok,
                             Err(panic_err) => {
@@ -382,6 +420,11 @@ fn main() {
                             }
                             TestType::Exec => {
                                 assert!(!transpile_return.parser_panicked);
+                                if !transpile_return.diagnostics.is_empty() {
+                                    return Err(FailureKind::UnexpectedDiagnostics(
+                                        transpile_return.diagnostics,
+                                    ));
+                                }
                                 let expected_exports = eval(&tsc_output.js)?;
                                 let actual_exports = eval(&output)?;
                                 if expected_exports != actual_exports {
@@ -0,0 +1,83 @@
+use std::cell::RefCell;
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{transpile, Allocator, SourceType, StringBuf, TranspileOptions, TranspileReturn};
+
+thread_local! {
+    // One allocator per worker thread, reset and reused for every source transpiled on that thread.
+    static ALLOCATOR: RefCell<Option<Allocator>> = const { RefCell::new(None) };
+}
+
+/// Transpiles `sources` in place in parallel on the rayon thread pool.
+///
+/// Each item is `(path, source_type, buffer)`. `path` is only passed through to identify the result.
+/// Results are returned in the same order as `sources`.
+///
+/// Each worker thread keeps an [`Allocator`], which is reset before each source and kept for later calls.
+pub fn transpile_many<P, S>(
+    sources: impl IntoIterator<Item = (P, SourceType, S)>,
+    options: &TranspileOptions,
+) -> Vec<(P, S, TranspileReturn)>
+where
+    P: Send,
+    S: StringBuf + Send,
+{
+    let sources: Vec<(P, SourceType, S)> = sources.into_iter().collect();
+    sources
+        .into_par_iter()
+        .map(|(path, source_type, mut source)| {
+            let ret = ALLOCATOR.with_borrow_mut(|allocator| {
+                let allocator = allocator.get_or_insert_with(Allocator::default);
+                allocator.reset();
+                transpile(allocator, source_type, &mut source, options)
+            });
+            (path, source, ret)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_in_order() {
+        let sources = (0..64).map(|index| {
+            (
+                index,
+                SourceType::ts(),
+                format!("let a{}: number = {}", index, index),
+            )
+        });
+        let results = transpile_many(sources, &TranspileOptions::default());
+        assert_eq!(results.len(), 64);
+        for (index, (path, output, ret)) in results.into_iter().enumerate() {
+            assert_eq!(path, index);
+            assert_eq!(output, format!("let a{}         = {}", index, index));
+            assert!(!ret.parser_panicked);
+        }
+    }
+
+    #[test]
+    fn allocator_reset() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let members = (0..2000)
+            .map(|index| format!("A{} = {},", index, index))
+            .collect::<String>();
+        let source = format!("enum E {{ {} }}", members);
+        let allocated_bytes = |count: usize| {
+            pool.install(|| {
+                let sources = (0..count).map(|index| (index, SourceType::ts(), source.clone()));
+                transpile_many(sources, &TranspileOptions::default());
+                ALLOCATOR.with_borrow(|allocator| allocator.as_ref().unwrap().allocated_bytes())
+            })
+        };
+        let allocated_for_one = allocated_bytes(1);
+        // Without resets, the allocator would grow with the number of sources
+        assert!(allocated_bytes(32) < allocated_for_one * 4);
+    }
+}
@@ -5,23 +5,65 @@ use std::fs::read_to_string;
 use std::process;
 
 use oxidase::oxc_diagnostics::NamedSource;
-use oxidase::{transpile, Allocator, SourceType};
+use oxidase::{compute_edits, try_transpile, Allocator, SourceType, TranspileOptions};
 
 fn main() {
-    let path = args().nth(1).unwrap_or_else(|| {
+    let mut explain = false;
+    let mut path: Option<String> = None;
+    for arg in args().skip(1) {
+        if arg == "--explain" {
+            explain = true;
+        } else {
+            path = Some(arg);
+        }
+    }
+    let path = path.unwrap_or_else(|| {
         concat!(env!("CARGO_MANIFEST_DIR"), "/src/bin/debug_input.ts").to_owned()
     });
 
-    let mut source = read_to_string(&path).unwrap();
+    let source = read_to_string(&path).unwrap();
 
     let allocator = Allocator::default();
-    let ret = transpile(&allocator, SourceType::ts(), &mut source);
-    if ret.parser_panicked {
-        for err in ret.parser_errors {
-            let named_source = NamedSource::new(&path, source.clone());
-            eprintln!("{:?}", err.with_source_code(named_source));
+    if explain {
+        // print each edit with its kind, the source it replaces and the replacement
+        let ret = compute_edits(
+            &allocator,
+            SourceType::ts(),
+            &source,
+            &TranspileOptions::default(),
+        );
+        for edit in &ret.edits {
+            println!(
+                "{}..{} {:?} {:?} -> {:?}",
+                edit.span.start,
+                edit.span.end,
+                edit.kind,
+                &source[edit.span.start as usize..edit.span.end as usize],
+                edit.replacement
+            );
+        }
+        if ret.transpile_return.parser_panicked {
+            for err in ret.transpile_return.parser_errors {
+                let named_source = NamedSource::new(&path, source.clone());
+                eprintln!("{:?}", err.with_source_code(named_source));
+            }
+            process::exit(1);
+        }
+        return;
+    }
+    match try_transpile(
+        &allocator,
+        SourceType::ts(),
+        &source,
+        &TranspileOptions::default(),
+    ) {
+        Ok(output) => println!("{}", output.code),
+        Err(err) => {
+            for error in err.errors() {
+                let named_source = NamedSource::new(&path, source.clone());
+                eprintln!("{:?}", error.to_diagnostic().with_source_code(named_source));
+            }
+            process::exit(1);
         }
-        process::exit(1);
     }
-    println!("{}", source);
 }
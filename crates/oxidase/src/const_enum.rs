@@ -0,0 +1,268 @@
+use hashbrown::HashMap;
+use oxc_allocator::Allocator;
+use oxc_diagnostics::OxcDiagnostic;
+use oxc_parser::{ParseOptions, Parser};
+use oxc_span::ast_alloc::VoidAllocator;
+use oxc_span::SourceType;
+
+use crate::enum_value::EnumValue;
+use crate::handler::StripHandler;
+
+/// Values of exported `const enum`s collected from a set of modules,
+/// for inlining accesses of imported `const enum` members in [`transpile`](crate::transpile).
+///
+/// Modules are identified by ids chosen by the caller, usually paths separated by `/`.
+/// Relative import specifiers are resolved against the id of the importing module
+/// ([`ImportedConstEnums::importer`]), trying TypeScript extensions like `.ts` and `/index.ts`.
+/// Other specifiers are matched against module ids verbatim.
+#[derive(Debug, Clone, Default)]
+pub struct ConstEnumRegistry {
+    modules: HashMap<String, ModuleConstEnums>,
+}
+
+/// Exported `const enum`s of a module: enum name -> member name -> value
+pub(crate) type ModuleConstEnums = HashMap<String, HashMap<String, ConstEnumValue>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ConstEnumValue {
+    Number(f64),
+    String(Box<str>),
+}
+
+impl ConstEnumValue {
+    pub(crate) fn as_enum_value(&self) -> EnumValue<'_> {
+        match self {
+            Self::Number(number) => EnumValue::Number(*number),
+            Self::String(string) => EnumValue::String(string),
+        }
+    }
+}
+
+/// Enables inlining `const enum`s imported from modules in [`registry`](Self::registry).
+/// See [`TranspileOptions::imported_const_enums`](crate::TranspileOptions::imported_const_enums).
+#[derive(Debug, Clone, Copy)]
+pub struct ImportedConstEnums<'a> {
+    pub registry: &'a ConstEnumRegistry,
+    /// Id of the module being transpiled, which relative import specifiers are resolved against.
+    pub importer: &'a str,
+}
+
+impl ConstEnumRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Collects exported `const enum`s in `source` as the module `module_id`.
+    /// Declaration files (`export declare const enum`) are supported.
+    ///
+    /// Returns the parser errors. Nothing is collected if the parser panicked.
+    pub fn collect(
+        &mut self,
+        allocator: &Allocator,
+        module_id: impl Into<String>,
+        source_type: SourceType,
+        source: &str,
+    ) -> Vec<OxcDiagnostic> {
+        // Unlike transpiling, ambient declarations can't be skipped because `declare const enum`s are collected.
+        let parser_options = ParseOptions {
+            allow_return_outside_function: true,
+            ..Default::default()
+        };
+        let parser = Parser::new(allocator, source, source_type).with_options(parser_options);
+        let handler = StripHandler::new_const_enum_collector(allocator, source);
+
+        const VOID_ALLOCATOR: VoidAllocator = VoidAllocator::new();
+        let mut parser_ret = parser.parse_with(&VOID_ALLOCATOR, handler);
+        if parser_ret.panicked {
+            return parser_ret.errors;
+        }
+
+        let module = self.modules.entry(module_id.into()).or_default();
+        for (enum_name, members) in parser_ret.handler.take_exported_const_enums() {
+            let enum_members = module.entry(enum_name.to_owned()).or_default();
+            for (member_name, value) in members {
+                let value = match value {
+                    EnumValue::Number(number) => ConstEnumValue::Number(number),
+                    EnumValue::String(string) => ConstEnumValue::String(string.into()),
+                };
+                enum_members.insert(member_name.to_owned(), value);
+            }
+        }
+        parser_ret.errors
+    }
+
+    /// Finds the module imported by `specifier` from `importer`.
+    pub(crate) fn resolve(&self, importer: &str, specifier: &str) -> Option<&ModuleConstEnums> {
+        if !(specifier.starts_with("./") || specifier.starts_with("../")) {
+            return self.modules.get(specifier);
+        }
+        let importer_dir = importer.rfind('/').map_or("", |index| &importer[..index]);
+        let path = join_path(importer_dir, specifier);
+
+        // `./a.js` refers to `./a.ts`
+        let (stem, extensions): (&str, &[&str]) = if let Some(stem) = path.strip_suffix(".js") {
+            (stem, &[".ts", ".tsx", ".d.ts", ".js"])
+        } else if let Some(stem) = path.strip_suffix(".mjs") {
+            (stem, &[".mts", ".d.mts", ".mjs"])
+        } else if let Some(stem) = path.strip_suffix(".cjs") {
+            (stem, &[".cts", ".d.cts", ".cjs"])
+        } else {
+            (
+                path.as_str(),
+                &[
+                    "",
+                    ".ts",
+                    ".tsx",
+                    ".d.ts",
+                    "/index.ts",
+                    "/index.tsx",
+                    "/index.d.ts",
+                ],
+            )
+        };
+        extensions.iter().find_map(|extension| {
+            let mut candidate = String::with_capacity(stem.len() + extension.len());
+            candidate.push_str(stem);
+            candidate.push_str(extension);
+            self.modules.get(&candidate)
+        })
+    }
+}
+
+/// Joins `dir` and the relative path `relative`, resolving `.` and `..` segments.
+fn join_path(dir: &str, relative: &str) -> String {
+    let mut segments: Vec<&str> = dir.split('/').collect();
+    if segments == [""] {
+        segments.clear();
+    }
+    for segment in relative.split('/') {
+        match segment {
+            "." => {}
+            ".." if matches!(segments.last(), Some(last) if !last.is_empty() && *last != "..") => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::transpile;
+    use crate::TranspileOptions;
+
+    #[test]
+    fn join_paths() {
+        assert_eq!(join_path("src/a", "./b"), "src/a/b");
+        assert_eq!(join_path("src/a", "../b/c"), "src/b/c");
+        assert_eq!(join_path("/src", "../../b"), "/../b");
+        assert_eq!(join_path("", "../b"), "../b");
+        assert_eq!(join_path("", "./b"), "b");
+    }
+
+    #[test]
+    fn resolve() {
+        let mut registry = ConstEnumRegistry::new();
+        for module_id in ["src/constants.ts", "src/shared/index.ts", "@app/constants"] {
+            registry
+                .modules
+                .insert(module_id.to_owned(), ModuleConstEnums::default());
+        }
+        let resolved = |importer: &str, specifier: &str| {
+            let module = registry.resolve(importer, specifier)?;
+            registry
+                .modules
+                .iter()
+                .find(|(_, candidate)| std::ptr::eq(*candidate, module))
+                .map(|(module_id, _)| module_id.as_str())
+        };
+        assert_eq!(
+            resolved("src/a.ts", "./constants"),
+            Some("src/constants.ts")
+        );
+        assert_eq!(
+            resolved("src/a/b.ts", "../constants.js"),
+            Some("src/constants.ts")
+        );
+        assert_eq!(
+            resolved("src/a.ts", "./shared"),
+            Some("src/shared/index.ts")
+        );
+        assert_eq!(
+            resolved("src/a.ts", "@app/constants"),
+            Some("@app/constants")
+        );
+        assert_eq!(resolved("src/a.ts", "./missing"), None);
+        assert_eq!(resolved("a.ts", "./constants"), None);
+    }
+
+    fn transpile_importer(registry: &ConstEnumRegistry, source: &str) -> String {
+        transpile(
+            source,
+            &TranspileOptions {
+                imported_const_enums: Some(ImportedConstEnums {
+                    registry,
+                    importer: "src/b.ts",
+                }),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn inline_imported() {
+        let allocator = Allocator::default();
+        let mut registry = ConstEnumRegistry::new();
+        let errors = registry.collect(
+            &allocator,
+            "src/a.ts",
+            SourceType::ts().with_module(true),
+            "export const enum Foo { A = 1, B = A << 1 }\nconst enum Bar { C }",
+        );
+        assert!(errors.is_empty());
+
+        let output = transpile_importer(
+            &registry,
+            "import { Foo } from './a';\nexport const a = [Foo.A, Foo.B];",
+        );
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0].trim(), "");
+        assert_eq!(lines[1], "export const a = [1    , 2    ];");
+
+        // Not exported
+        let output = transpile_importer(&registry, "import { Bar } from './a';\nBar.C;");
+        assert_eq!(output, "import { Bar } from './a';\nBar.C;");
+
+        // Referenced other than through member accesses
+        let output = transpile_importer(&registry, "import { Foo } from './a';\nf(Foo, Foo.A);");
+        assert_eq!(output, "import { Foo } from './a';\nf(Foo, 1    );");
+    }
+
+    #[test]
+    fn shadowed_import() {
+        let allocator = Allocator::default();
+        let mut registry = ConstEnumRegistry::new();
+        registry.collect(
+            &allocator,
+            "src/a.ts",
+            SourceType::ts().with_module(true),
+            "export const enum Foo { A = 1 }",
+        );
+
+        let output = transpile_importer(
+            &registry,
+            "import { Foo } from './a';\nexport function f(Foo) { return Foo.A }\nexport const a = Foo.A;",
+        );
+        let lines = output.lines().collect::<Vec<_>>();
+        // The shadowing parameter doesn't reference the import
+        assert_eq!(lines[0].trim(), "");
+        assert_eq!(lines[1], "export function f(Foo) { return Foo.A }");
+        assert_eq!(lines[2], "export const a = 1    ;");
+    }
+}
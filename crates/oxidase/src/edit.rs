@@ -0,0 +1,48 @@
+use oxc_span::Span;
+
+use crate::patch::{write_whitespaces_preserving_newlines, Patch, PatchKind};
+
+/// An edit that turns the input into the output of [`transpile`](crate::transpile).
+///
+/// `replacement` is written at the start of `span`. If it's shorter than `span`, the rest of `span` is blanked:
+/// each byte is replaced with a space, except line terminators (`\n`, `\r`, U+2028 and U+2029), which are kept,
+/// so that line positions are preserved. `replacement` never contains line terminators.
+///
+/// Edits returned by [`compute_edits`](crate::compute_edits) are sorted and never overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit<'a> {
+    pub span: Span,
+    pub replacement: &'a str,
+    /// Why the edit is generated. For diagnostic purposes only.
+    pub kind: PatchKind,
+}
+
+impl<'a> Edit<'a> {
+    /// The full text that replaces `span` in `source`, i.e. `replacement` followed by the blanked rest of `span`.
+    pub fn new_text(&self, source: &str) -> String {
+        let start = self.span.start as usize;
+        let end = self.span.end as usize;
+        let blank_start = (start + self.replacement.len()).min(end);
+        let mut new_text = String::with_capacity(self.replacement.len() + (end - blank_start));
+        new_text.push_str(self.replacement);
+        // SAFETY: only spaces and line terminators copied from `source` are written. Writing to a Vec never fails.
+        unsafe {
+            write_whitespaces_preserving_newlines(
+                &source.as_bytes()[blank_start..end],
+                new_text.as_mut_vec(),
+            )
+        }
+        .unwrap();
+        new_text
+    }
+}
+
+impl<'a> From<Patch<'a>> for Edit<'a> {
+    fn from(patch: Patch<'a>) -> Self {
+        Self {
+            span: patch.span,
+            replacement: patch.replacement,
+            kind: patch.kind,
+        }
+    }
+}
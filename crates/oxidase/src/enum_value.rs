@@ -0,0 +1,240 @@
+use std::fmt::{self, Write as _};
+
+use oxc_syntax::operator::{BinaryOperator, UnaryOperator};
+
+/// The value of a constant enum member initializer, evaluated the same way as TypeScript.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnumValue<'a> {
+    Number(f64),
+    String(&'a str),
+}
+
+impl<'a> EnumValue<'a> {
+    pub fn eval_unary(operator: UnaryOperator, argument: Self) -> Option<Self> {
+        let Self::Number(argument) = argument else {
+            return None;
+        };
+        Some(Self::Number(match operator {
+            UnaryOperator::UnaryPlus => argument,
+            UnaryOperator::UnaryNegation => -argument,
+            UnaryOperator::BitwiseNot => !to_int32(argument) as f64,
+            _ => return None,
+        }))
+    }
+
+    /// `concat` allocates the result of string concatenations.
+    pub fn eval_binary(
+        operator: BinaryOperator,
+        left: Self,
+        right: Self,
+        concat: impl FnOnce(&str, &str) -> &'a str,
+    ) -> Option<Self> {
+        let (left, right) = match (left, right) {
+            (Self::Number(left), Self::Number(right)) => (left, right),
+            (left, right) if operator == BinaryOperator::Addition => {
+                return Some(Self::String(concat(
+                    &left.to_js_string(),
+                    &right.to_js_string(),
+                )));
+            }
+            _ => return None,
+        };
+        Some(Self::Number(match operator {
+            BinaryOperator::Addition => left + right,
+            BinaryOperator::Subtraction => left - right,
+            BinaryOperator::Multiplication => left * right,
+            BinaryOperator::Division => left / right,
+            BinaryOperator::Remainder => left % right,
+            BinaryOperator::Exponential => left.powf(right),
+            BinaryOperator::ShiftLeft => to_int32(left).wrapping_shl(to_uint32(right)) as f64,
+            BinaryOperator::ShiftRight => to_int32(left).wrapping_shr(to_uint32(right)) as f64,
+            BinaryOperator::ShiftRightZeroFill => {
+                to_uint32(left).wrapping_shr(to_uint32(right)) as f64
+            }
+            BinaryOperator::BitwiseOR => (to_int32(left) | to_int32(right)) as f64,
+            BinaryOperator::BitwiseXOR => (to_int32(left) ^ to_int32(right)) as f64,
+            BinaryOperator::BitwiseAnd => (to_int32(left) & to_int32(right)) as f64,
+            _ => return None,
+        }))
+    }
+
+    /// Writes the value as a JavaScript literal. Negative numbers are written with a leading `-`.
+    pub fn write_js_literal(self, out: &mut impl fmt::Write) -> fmt::Result {
+        match self {
+            Self::Number(number) if number == 0.0 && number.is_sign_negative() => {
+                out.write_str("-0")
+            }
+            Self::Number(number) => out.write_str(&number_to_js_string(number)),
+            Self::String(string) => {
+                out.write_char('"')?;
+                for ch in string.chars() {
+                    match ch {
+                        '"' => out.write_str("\\\"")?,
+                        '\\' => out.write_str("\\\\")?,
+                        '\n' => out.write_str("\\n")?,
+                        '\r' => out.write_str("\\r")?,
+                        '\t' => out.write_str("\\t")?,
+                        '\u{2028}' | '\u{2029}' => write!(out, "\\u{:04x}", ch as u32)?,
+                        ch if (ch as u32) < 0x20 => write!(out, "\\x{:02x}", ch as u32)?,
+                        ch => out.write_char(ch)?,
+                    }
+                }
+                out.write_char('"')
+            }
+        }
+    }
+
+    fn to_js_string(self) -> std::string::String {
+        match self {
+            Self::Number(number) => number_to_js_string(number),
+            Self::String(string) => string.to_owned(),
+        }
+    }
+}
+
+/// https://tc39.es/ecma262/#sec-toint32
+fn to_int32(number: f64) -> i32 {
+    to_uint32(number) as i32
+}
+
+/// https://tc39.es/ecma262/#sec-touint32
+fn to_uint32(number: f64) -> u32 {
+    if !number.is_finite() {
+        return 0;
+    }
+    number.trunc().rem_euclid(4294967296.0) as u32
+}
+
+/// https://tc39.es/ecma262/#sec-numeric-types-number-tostring
+pub fn number_to_js_string(number: f64) -> std::string::String {
+    if number.is_nan() {
+        return "NaN".to_owned();
+    }
+    if number == 0.0 {
+        return "0".to_owned();
+    }
+    if number.is_infinite() {
+        return if number > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
+        }
+        .to_owned();
+    }
+    if number < 0.0 {
+        return format!("-{}", number_to_js_string(-number));
+    }
+    // `{:e}` gives the shortest digits that roundtrip, like JavaScript.
+    let exponential = format!("{:e}", number);
+    let (mantissa, exponent) = exponential.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let exponent: i32 = exponent.parse().unwrap();
+    let digit_count = digits.len() as i32;
+    // the position of the decimal point relative to the start of digits
+    let point = exponent + 1;
+    if digit_count <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - digit_count) as usize))
+    } else if 0 < point && point <= 21 {
+        format!(
+            "{}.{}",
+            &digits[..point as usize],
+            &digits[point as usize..]
+        )
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let sign = if point - 1 < 0 { '-' } else { '+' };
+        let exponent = (point - 1).abs();
+        if digit_count == 1 {
+            format!("{}e{}{}", digits, sign, exponent)
+        } else {
+            format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, exponent)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn js_number_strings() {
+        for (number, expected) in [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (1e21, "1e+21"),
+            (123e19, "1.23e+21"),
+            (1e20, "100000000000000000000"),
+            (1e-6, "0.000001"),
+            (1e-7, "1e-7"),
+            (1.5e-7, "1.5e-7"),
+            (f64::NAN, "NaN"),
+            (f64::NEG_INFINITY, "-Infinity"),
+        ] {
+            assert_eq!(number_to_js_string(number), expected);
+        }
+    }
+
+    #[test]
+    fn int32_conversions() {
+        assert_eq!(to_int32(4294967296.0 + 5.0), 5);
+        assert_eq!(to_int32(2147483648.0), -2147483648);
+        assert_eq!(to_int32(-1.5), -1);
+        assert_eq!(to_uint32(-1.0), 4294967295);
+        assert_eq!(to_uint32(f64::NAN), 0);
+    }
+
+    #[test]
+    fn js_literals() {
+        let literal = |value: EnumValue<'_>| {
+            let mut out = std::string::String::new();
+            value.write_js_literal(&mut out).unwrap();
+            out
+        };
+        assert_eq!(literal(EnumValue::Number(1.5)), "1.5");
+        assert_eq!(literal(EnumValue::Number(-0.0)), "-0");
+        assert_eq!(literal(EnumValue::Number(f64::NEG_INFINITY)), "-Infinity");
+        assert_eq!(
+            literal(EnumValue::String("a\"b\\\n\u{1}\u{2028}")),
+            r#""a\"b\\\n\x01\u2028""#
+        );
+    }
+
+    #[test]
+    fn binary() {
+        let concat = |_: &str, _: &str| -> &'static str { unreachable!() };
+        assert_eq!(
+            EnumValue::eval_binary(
+                BinaryOperator::ShiftLeft,
+                EnumValue::Number(1.0),
+                EnumValue::Number(33.0),
+                concat
+            ),
+            Some(EnumValue::Number(2.0))
+        );
+        assert_eq!(
+            EnumValue::eval_binary(
+                BinaryOperator::ShiftRightZeroFill,
+                EnumValue::Number(-1.0),
+                EnumValue::Number(0.0),
+                concat
+            ),
+            Some(EnumValue::Number(4294967295.0))
+        );
+        assert_eq!(
+            EnumValue::eval_binary(
+                BinaryOperator::Addition,
+                EnumValue::String("a"),
+                EnumValue::Number(1.0),
+                |left, right| {
+                    assert_eq!((left, right), ("a", "1"));
+                    "a1"
+                }
+            ),
+            Some(EnumValue::String("a1"))
+        );
+    }
+}
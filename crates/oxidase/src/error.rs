@@ -0,0 +1,98 @@
+use std::fmt;
+
+use oxc_diagnostics::{LabeledSpan, OxcDiagnostic};
+
+/// A byte range in the source that an error points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorLabel {
+    pub start: u32,
+    pub end: u32,
+    pub message: Option<String>,
+}
+
+/// An error message with the source ranges it points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorInfo {
+    pub message: String,
+    pub labels: Vec<ErrorLabel>,
+    pub help: Option<String>,
+}
+
+impl ErrorInfo {
+    /// Converts back to an [`OxcDiagnostic`], which can be rendered with the source code.
+    pub fn to_diagnostic(&self) -> OxcDiagnostic {
+        let mut diagnostic = OxcDiagnostic::error(self.message.clone()).with_labels(
+            self.labels.iter().map(|label| {
+                LabeledSpan::new(
+                    label.message.clone(),
+                    label.start as usize,
+                    (label.end - label.start) as usize,
+                )
+            }),
+        );
+        if let Some(help) = &self.help {
+            diagnostic = diagnostic.with_help(help.clone());
+        }
+        diagnostic
+    }
+}
+
+impl From<&OxcDiagnostic> for ErrorInfo {
+    fn from(diagnostic: &OxcDiagnostic) -> Self {
+        Self {
+            message: diagnostic.message.to_string(),
+            labels: diagnostic
+                .labels
+                .iter()
+                .flatten()
+                .map(|label| ErrorLabel {
+                    start: label.offset() as u32,
+                    end: (label.offset() + label.len()) as u32,
+                    message: label.label().map(str::to_owned),
+                })
+                .collect(),
+            help: diagnostic.help.as_ref().map(|help| help.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Why [`try_transpile`](crate::try_transpile) failed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind")
+)]
+pub enum TranspileError {
+    /// The parser couldn't recover from syntax errors.
+    #[error("Failed to parse: {}", first_message(errors))]
+    Parse { errors: Vec<ErrorInfo> },
+    /// Syntax that can't be transpiled with the given options,
+    /// e.g. non-erasable syntax in [`TranspileOptions::erasable_syntax_only`](crate::TranspileOptions::erasable_syntax_only) mode.
+    #[error("Unsupported syntax: {}", first_message(errors))]
+    UnsupportedSyntax { errors: Vec<ErrorInfo> },
+    /// The transpiler failed its own invariants. This is a bug.
+    #[error("Internal error: {error}")]
+    Internal { error: ErrorInfo },
+}
+
+impl TranspileError {
+    pub fn errors(&self) -> &[ErrorInfo] {
+        match self {
+            Self::Parse { errors } | Self::UnsupportedSyntax { errors } => errors,
+            Self::Internal { error } => std::slice::from_ref(error),
+        }
+    }
+}
+
+fn first_message(errors: &[ErrorInfo]) -> &str {
+    errors.first().map_or("", |error| &error.message)
+}
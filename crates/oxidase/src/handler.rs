@@ -1,8 +1,12 @@
 use std::fmt::Write;
 use std::ops::Range;
 
-use crate::patch::Patch;
+use crate::const_enum::ConstEnumValue;
+use crate::enum_value::EnumValue;
+use crate::line_term::contains_line_terminators;
+use crate::patch::{Patch, PatchKind};
 use crate::patch_builder::PatchBuilder;
+use crate::{ModuleFormat, TranspileOptions};
 use bumpalo::{format, Bump};
 use hashbrown::hash_map::DefaultHashBuilder;
 use hashbrown::{HashMap, HashSet};
@@ -10,6 +14,7 @@ use oxc_allocator::{Allocator, String, Vec};
 use oxc_ast::handle::Handler as AstHandler;
 use oxc_ast::{ast::*, AstScopeNode, ScopeType};
 use oxc_data_structures::stack::NonEmptyStack;
+use oxc_diagnostics::OxcDiagnostic;
 use oxc_parser::Handler as ParserHandler;
 use oxc_span::ast_alloc::AstAllocator;
 use oxc_span::GetSpan;
@@ -24,18 +29,68 @@ impl SpanExt for Span {
     }
 }
 
+/// Declarations of the helpers for `TranspileOptions::experimental_decorators`, equivalent to the ones emitted by tsc
+const DECORATE_HELPER: &str = ";function __decorate(decorators,target,key,desc){var c=arguments.length,r=c<3?target:desc===null?desc=Object.getOwnPropertyDescriptor(target,key):desc,d;if(typeof Reflect===\"object\"&&typeof Reflect.decorate===\"function\")r=Reflect.decorate(decorators,target,key,desc);else for(var i=decorators.length-1;i>=0;i--)if(d=decorators[i])r=(c<3?d(r):c>3?d(target,key,r):d(target,key))||r;return c>3&&r&&Object.defineProperty(target,key,r),r}";
+const PARAM_HELPER: &str = ";function __param(paramIndex,decorator){return function(target,key){decorator(target,key,paramIndex)}}";
+const METADATA_HELPER: &str = ";function __metadata(k,v){if(typeof Reflect===\"object\"&&typeof Reflect.metadata===\"function\")return Reflect.metadata(k,v)}";
+
 pub struct StripHandler<'source, 'alloc> {
     source: &'source str,
     allocator: &'alloc Allocator,
+    options: TranspileOptions<'source>,
 
     patches: PatchBuilder<'alloc>,
     scope_stack: NonEmptyStack<Scope<'alloc>>,
+    diagnostics: std::vec::Vec<OxcDiagnostic>,
+
+    /// Names of `const enum`s declared so far, whose rebindings in nested scopes are tracked in `Scope::bindings`
+    const_enum_names: HashSet<&'alloc str, DefaultHashBuilder, &'alloc Bump>,
+    /// Names of exported `const enum`s in the root scope, for collecting `ConstEnumRegistry`
+    exported_const_enum_names: Vec<'alloc, &'alloc str>,
+    /// Specifiers of the import declaration being handled
+    current_import_specifiers: Vec<'alloc, CurrentImportSpecifier<'alloc>>,
+    /// `const enum`s imported from modules in `TranspileOptions::imported_const_enums` by their local names
+    imported_const_enums:
+        HashMap<&'alloc str, ImportedConstEnum<'source, 'alloc>, DefaultHashBuilder, &'alloc Bump>,
+    /// Import declarations containing `imported_const_enums`
+    const_enum_import_decls: Vec<'alloc, ConstEnumImportDeclaration>,
+    /// References to `const enum`s erased by `TranspileOptions::erase_const_enums`, by enum names
+    erased_const_enum_references:
+        HashMap<&'alloc str, Vec<'alloc, Span>, DefaultHashBuilder, &'alloc Bump>,
+    /// `,__param(0,a)` of the decorated constructor parameters of the class being handled, taken from its scope in `handle_class_body`
+    constructor_param_decorators: &'alloc str,
+    /// Types of the constructor parameters of the class being handled, taken from its scope in `handle_class_body`
+    constructor_param_types: Option<&'alloc str>,
+    /// Decorated members of the class being handled, taken from its scope in `handle_class_body`
+    decorated_members: &'alloc [DecoratedMember<'alloc>],
+    /// Types handled so far, serialized for decorator metadata (`Number`, `Object`...), sorted by spans.
+    /// Types nested in others are removed. Only tracked with `emit_decorator_metadata`.
+    type_metadata: Vec<'alloc, (Span, &'alloc str)>,
+    /// Start of the first decorator of the last class declaration handled, if its decorators are lowered
+    first_class_decorator_start: Option<u32>,
+    /// End of the last top-level statement, where the decorator helpers are declared
+    last_top_level_statement_end: u32,
+    uses_decorate_helper: bool,
+    uses_param_helper: bool,
+    uses_metadata_helper: bool,
+    /// Whether `__decorate`/`__param`/`__metadata` are declared or imported at the top level (e.g. from `tslib`)
+    declares_decorate_helper: bool,
+    declares_param_helper: bool,
+    declares_metadata_helper: bool,
+    /// Only records `const enum` members for `ConstEnumRegistry`, without generating the code of enums
+    collect_const_enums_only: bool,
+    /// Identifier-like words of the source, which generated names must avoid. Collected on the first generated name.
+    source_words: Option<HashSet<&'source str, DefaultHashBuilder, &'alloc Bump>>,
+    /// Spans of identifier references in class bodies, to find decorated classes referencing themselves.
+    /// Only tracked with `experimental_decorators`, and cleared after each outermost class.
+    class_body_references: Vec<'alloc, Span>,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct StripHandlerCheckpoint {
     patch_len: u32,
     scope_stack_len: u32,
+    diagnostic_len: u32,
 }
 
 #[derive(Debug)]
@@ -64,13 +119,62 @@ struct CurrentEnumDeclaration<'alloc> {
     enum_name: &'alloc str,
     index_of_patch_before_enum_name: usize,
     is_secondary: bool,
+    is_const: bool,
+    /// Whether no code is emitted for the enum (`declare enum`, or `const enum` with `TranspileOptions::erase_const_enums`),
+    /// so later enums and namespaces of the same name can't be merged into it
+    is_erased: bool,
+    /// Member code deferred to the end of the last member line, moved from `EnumScope` when leaving the enum scope
+    pending_member_code: Option<PendingEnumMemberCode<'alloc>>,
+}
+
+#[derive(Debug)]
+struct PendingEnumMemberCode<'alloc> {
+    code: &'alloc str,
+    last_member_end_patch_index: usize,
+}
+
+#[derive(Debug)]
+struct CurrentImportSpecifier<'alloc> {
+    span: Span,
+    /// None for default and namespace specifiers
+    imported_and_local_names: Option<(&'alloc str, &'alloc str)>,
+    is_type: bool,
+}
+
+#[derive(Debug)]
+struct ImportedConstEnum<'source, 'alloc> {
+    members: &'source std::collections::HashMap<std::string::String, ConstEnumValue>,
+    specifier_span: Span,
+    decl_index: usize,
+    /// Spans of all references to the local name, including the inlined ones
+    reference_spans: Vec<'alloc, Span>,
+}
+
+#[derive(Debug)]
+struct ConstEnumImportDeclaration {
+    span: Span,
+    /// Number of specifiers that are not type-only
+    value_specifier_count: usize,
 }
 
 #[derive(Debug)]
 struct CurrentNamespaceDeclaration<'alloc> {
     namespace_name: &'alloc str,
     index_of_patch_before_namespace_name: usize,
+    /// Whether a class, function, enum or namespace of the same name is declared before in the same scope,
+    /// in which case `var` is omitted and members are attached to the existing binding.
+    is_merged: bool,
     is_ambient: bool,
+    /// Export assignments deferred to the end of the last statement line, moved from `NamespaceScope` when leaving the namespace scope
+    pending_exports: Option<PendingNamespaceExports<'alloc>>,
+    /// Moved from `NamespaceScope` when leaving the namespace scope
+    param_name: Option<&'alloc str>,
+}
+
+#[derive(Debug)]
+struct PendingNamespaceExports<'alloc> {
+    code: &'alloc str,
+    pos: u32,
 }
 
 #[derive(Debug)]
@@ -83,8 +187,47 @@ struct Scope<'alloc> {
         DefaultHashBuilder,
         &'alloc Bump,
     >,
+    /// Constant member values of `const enum`s declared in this scope, for inlining member accesses
+    const_enum_members_by_enum_names: HashMap<
+        &'alloc str,
+        Vec<'alloc, (&'alloc str, EnumValue<'alloc>)>,
+        DefaultHashBuilder,
+        &'alloc Bump,
+    >,
+    /// Names of classes and functions declared in this scope, which namespaces of the same names are merged into
+    class_and_function_names: HashSet<&'alloc str, DefaultHashBuilder, &'alloc Bump>,
+    /// Exported names of namespaces declared in this scope, which are resolved as `N.x` in later blocks of the same namespace
+    exported_names_by_namespace_names: HashMap<
+        &'alloc str,
+        HashSet<&'alloc str, DefaultHashBuilder, &'alloc Bump>,
+        DefaultHashBuilder,
+        &'alloc Bump,
+    >,
+    /// Names of namespaces with only types declared in this scope, whose `import X = N.Y` aliases are erased
+    type_only_namespace_names: HashSet<&'alloc str, DefaultHashBuilder, &'alloc Bump>,
     current_enum_decl: Option<CurrentEnumDeclaration<'alloc>>,
     current_namespace_decl: Option<CurrentNamespaceDeclaration<'alloc>>,
+    /// Whether the scope is in a transformed namespace, where references to exported mutable variables are tracked
+    in_namespace: bool,
+    /// Whether the scope is a nested block (block statements, `switch` cases and `catch` clauses),
+    /// where enums and namespaces are declared with `let` instead of `var`
+    is_block: bool,
+    /// Whether `var` declarations in this scope and its nested blocks belong to it (programs, functions, static blocks and namespaces)
+    is_var_scope: bool,
+    /// Names declared in this scope and their spans. Only tracked in namespaces, and for names of `const enum`s to detect shadowing.
+    bindings: Vec<'alloc, (&'alloc str, Span)>,
+    /// References not declared in this scope or its descendants yet. Only tracked in namespaces.
+    unresolved_references: Vec<'alloc, NamespaceReference>,
+    /// Spans of decorators (`@a`) not yet taken by the classes, class members or parameters they decorate
+    decorators: Vec<'alloc, Span>,
+}
+
+/// An identifier reference in a namespace
+#[derive(Debug, Clone, Copy)]
+struct NamespaceReference {
+    span: Span,
+    /// `{ x }` or `({ x } = a)`, which needs to be expanded to `{ x: N.x }`
+    is_shorthand: bool,
 }
 
 #[derive(Debug)]
@@ -93,6 +236,47 @@ struct ClassScope<'alloc> {
     parameter_prop_id_spans_in_first_constructor: Vec<'alloc, Span>,
     parameter_prop_id_spans: Vec<'alloc, Span>,
     parameter_prop_init_insert_start: Option<u32>,
+    /// Where to insert `this.a=a;` of parameter properties in the constructor, and the code.
+    /// Inserted in `handle_class_body`, along with `field_inits`.
+    constructor_init: Option<(Span, &'alloc str)>,
+    /// `this.a=this.#init_0();` of fields with initializers without `use_define_for_class_fields`
+    field_inits: String<'alloc>,
+    /// `this.a=this.#init_0();` of static fields with initializers without `use_define_for_class_fields`,
+    /// inserted in a static block at the end of the class body
+    static_field_inits: String<'alloc>,
+    /// Suffix of the next private method wrapping a field initializer (`#init_0`)
+    next_field_init_suffix: u32,
+    /// Parameters of the method being handled with `experimental_decorators`
+    method_params: Vec<'alloc, MethodParameter<'alloc>>,
+    /// `,__param(0,a)` of decorated constructor parameters with `experimental_decorators`, applied with class decorators
+    constructor_param_decorators: String<'alloc>,
+    /// Types of constructor parameters for `design:paramtypes` with `emit_decorator_metadata`
+    constructor_param_types: Option<&'alloc str>,
+    /// Members decorated with `experimental_decorators`, whose decorators are applied after the class
+    decorated_members: Vec<'alloc, DecoratedMember<'alloc>>,
+}
+
+/// A class member decorated with `experimental_decorators`
+#[derive(Debug)]
+struct DecoratedMember<'alloc> {
+    /// Spans of the decorators (`@a`), which are copied and erased in `handle_class`
+    decorators: &'alloc [Span],
+    /// The key argument of `__decorate` (`"m"`)
+    key: &'alloc str,
+    is_static: bool,
+    /// The descriptor argument of `__decorate`: `null` for methods and accessors, `void 0` for properties
+    descriptor: &'static str,
+    /// `,__param(0,a)` of decorated parameters and `,__metadata(...)` of the member, following its decorators
+    extra_decorators: &'alloc str,
+}
+
+#[derive(Debug)]
+struct MethodParameter<'alloc> {
+    span: Span,
+    /// Copied expressions of the decorators
+    decorators: Vec<'alloc, &'alloc str>,
+    /// Serialized type for `design:paramtypes` with `emit_decorator_metadata`
+    type_metadata: &'alloc str,
 }
 
 #[derive(Debug)]
@@ -106,6 +290,17 @@ struct FunctionWithParamPropsScope<'alloc> {
 #[derive(Debug)]
 struct EnumScope<'alloc> {
     member_names: Vec<'alloc, EnumName<'alloc>>,
+    /// Values of constant expressions in the current member initializer, by their spans
+    expr_values: Vec<'alloc, (Span, EnumValue<'alloc>)>,
+    /// Index of the patch replacing the current member name if it's not an identifier
+    current_member_name_patch_index: Option<usize>,
+    /// Whether the initializer of the current member references the enum object
+    current_member_references_enum: bool,
+    /// Code of identifier members (`var A;E[E.A=A]='A';`) deferred to the end of their line,
+    /// so that members after them on the same line keep their columns.
+    pending_member_code: String<'alloc>,
+    /// Index of the patch at the end of the last member, where `pending_member_code` is appended
+    last_member_end_patch_index: usize,
 }
 
 #[derive(Debug)]
@@ -113,6 +308,21 @@ struct NamespaceScope<'alloc> {
     current_stmt_binding_identifiers: Vec<'alloc, &'alloc str>,
     // exported_identifiers: Vec<'alloc, &'alloc str>,
     is_ambient: bool,
+    /// Export assignments (`N.a=a;`) deferred to the end of their line,
+    /// so that statements after them on the same line keep their columns.
+    pending_exports: String<'alloc>,
+    /// Where `pending_exports` are inserted
+    pending_exports_pos: u32,
+    /// Positions of export assignments already inserted
+    export_positions: Vec<'alloc, u32>,
+    /// Names exported by this block of the namespace
+    exported_names: Vec<'alloc, &'alloc str>,
+    /// Exported `let`/`var`s, and the spans of their declaring statements.
+    /// Their references elsewhere are rewritten as `N.x`, so that the namespace property is the only storage.
+    mutable_exports: Vec<'alloc, (&'alloc str, Span)>,
+    /// Parameter of the generated function if it's not the namespace name,
+    /// which happens when a declaration in the namespace shadows the namespace name (`namespace N { const N = 1 }`).
+    param_name: Option<&'alloc str>,
 }
 
 #[derive(Debug)]
@@ -128,6 +338,8 @@ enum ScopeKind<'alloc> {
 struct EnumName<'alloc> {
     value: &'alloc str,
     is_identifier: bool,
+    /// None if the member initializer is not constant
+    const_value: Option<EnumValue<'alloc>>,
 }
 
 #[derive(Debug)]
@@ -138,29 +350,1184 @@ enum PrologueScanState {
 }
 
 impl<'source, 'alloc> StripHandler<'source, 'alloc> {
-    pub fn new(allocator: &'alloc Allocator, source: &'source str) -> Self {
+    pub fn new(
+        allocator: &'alloc Allocator,
+        source: &'source str,
+        mut options: TranspileOptions<'source>,
+    ) -> Self {
+        if options.erasable_syntax_only {
+            options.transform_enums = false;
+            options.transform_namespaces = false;
+            options.transform_parameter_properties = false;
+        }
         Self {
             source,
+            options,
+            diagnostics: std::vec::Vec::new(),
             patches: PatchBuilder::new(allocator),
             allocator,
+            const_enum_names: HashSet::new_in(allocator),
+            exported_const_enum_names: Vec::new_in(allocator),
+            current_import_specifiers: Vec::new_in(allocator),
+            imported_const_enums: HashMap::new_in(allocator),
+            const_enum_import_decls: Vec::new_in(allocator),
+            erased_const_enum_references: HashMap::new_in(allocator),
+            constructor_param_decorators: "",
+            constructor_param_types: None,
+            decorated_members: &[],
+            type_metadata: Vec::new_in(allocator),
+            first_class_decorator_start: None,
+            last_top_level_statement_end: 0,
+            uses_decorate_helper: false,
+            uses_param_helper: false,
+            uses_metadata_helper: false,
+            declares_decorate_helper: false,
+            declares_param_helper: false,
+            declares_metadata_helper: false,
+            collect_const_enums_only: false,
+            source_words: None,
+            class_body_references: Vec::new_in(allocator),
             scope_stack: NonEmptyStack::with_capacity(
                 32,
                 Scope {
                     last_statement: None,
                     kind: ScopeKind::Other,
                     member_identifiers_by_enum_names: HashMap::new_in(allocator),
+                    const_enum_members_by_enum_names: HashMap::new_in(allocator),
+                    class_and_function_names: HashSet::new_in(allocator),
+                    exported_names_by_namespace_names: HashMap::new_in(allocator),
+                    type_only_namespace_names: HashSet::new_in(allocator),
                     current_enum_decl: None,
                     current_namespace_decl: None,
+                    in_namespace: false,
+                    is_block: false,
+                    is_var_scope: true,
+                    bindings: Vec::new_in(allocator),
+                    unresolved_references: Vec::new_in(allocator),
+                    decorators: Vec::new_in(allocator),
                 },
             ),
         }
     }
+    /// Creates a handler that only collects `const enum`s for [`take_exported_const_enums`](Self::take_exported_const_enums).
+    /// Other transforms are disabled, and no code is generated for enums.
+    pub fn new_const_enum_collector(allocator: &'alloc Allocator, source: &'source str) -> Self {
+        let options = TranspileOptions {
+            transform_namespaces: false,
+            transform_parameter_properties: false,
+            ..Default::default()
+        };
+        let mut handler = Self::new(allocator, source, options);
+        handler.collect_const_enums_only = true;
+        handler
+    }
     pub fn scope_len(&self) -> usize {
         self.scope_stack.len() - 1
     }
+    /// Generates code that depends on the whole file. Called once after parsing.
+    pub fn finish(&mut self) {
+        self.erase_inlined_const_enum_imports();
+        self.report_erased_const_enum_references();
+        self.declare_decorator_helpers();
+    }
     pub fn into_patches(self) -> Vec<'alloc, Patch<'alloc>> {
         self.patches.into_patches()
     }
+    /// Takes exported `const enum`s in the root scope and their constant members.
+    pub fn take_exported_const_enums(
+        &mut self,
+    ) -> std::vec::Vec<(&'alloc str, Vec<'alloc, (&'alloc str, EnumValue<'alloc>)>)> {
+        debug_assert_eq!(self.scope_len(), 0);
+        let exported_const_enum_names = &self.exported_const_enum_names;
+        self.scope_stack
+            .last_mut()
+            .const_enum_members_by_enum_names
+            .drain()
+            .filter(|(enum_name, _)| exported_const_enum_names.contains(enum_name))
+            .collect()
+    }
+    pub fn take_diagnostics(&mut self) -> std::vec::Vec<OxcDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Reports `span` as a non-erasable construct in erasable-syntax-only mode.
+    /// Returns true if reported, in which case no code should be generated for it.
+    fn report_non_erasable(&mut self, span: Span, message: &'static str) -> bool {
+        if !self.options.erasable_syntax_only {
+            return false;
+        }
+        self.diagnostics.push(
+            OxcDiagnostic::error(message)
+                .with_label(span)
+                .with_help("This syntax is not allowed in erasable-syntax-only mode"),
+        );
+        true
+    }
+
+    /// Whether the current scope is an enum, where constant expressions in member initializers are evaluated.
+    fn in_enum_scope(&self) -> bool {
+        matches!(self.scope_stack.last().kind, ScopeKind::Enum(_))
+    }
+
+    /// Records the value of a constant expression if it's in an enum member initializer.
+    fn push_enum_expr_value(&mut self, span: Span, value: EnumValue<'alloc>) {
+        if let ScopeKind::Enum(EnumScope { expr_values, .. }) =
+            &mut self.scope_stack.last_mut().kind
+        {
+            expr_values.push((span, value));
+        }
+    }
+
+    /// Takes the value of the constant expression at `span` in the current enum member initializer.
+    fn take_enum_expr_value(&mut self, span: Span) -> Option<EnumValue<'alloc>> {
+        let ScopeKind::Enum(EnumScope { expr_values, .. }) = &mut self.scope_stack.last_mut().kind
+        else {
+            return None;
+        };
+        let index = expr_values
+            .iter()
+            .rposition(|(value_span, _)| *value_span == span)?;
+        Some(expr_values.remove(index).1)
+    }
+
+    /// Finds the value of a previous member of the current enum by its name.
+    /// Returns `Some(None)` if the member is found but not constant.
+    fn enum_member_value(&self, name: &str) -> Option<Option<EnumValue<'alloc>>> {
+        let ScopeKind::Enum(EnumScope { member_names, .. }) = &self.scope_stack.last().kind else {
+            return None;
+        };
+        // the last one is the current member
+        let previous_member_names = &member_names[..member_names.len().saturating_sub(1)];
+        previous_member_names
+            .iter()
+            .rev()
+            .find(|member_name| member_name.is_identifier && member_name.value == name)
+            .map(|member_name| member_name.const_value)
+    }
+
+    /// Declaration of the namespace whose scope is the current one
+    /// (`None` for `declare global`/`declare module 'a'`)
+    fn current_namespace_decl_in_parent(&self) -> Option<&CurrentNamespaceDeclaration<'alloc>> {
+        self.scope_stack[self.scope_stack.len() - 2]
+            .current_namespace_decl
+            .as_ref()
+    }
+
+    /// Parameter of the function generated for the namespace whose scope is the current one.
+    fn current_namespace_param_name(&self) -> Option<&'alloc str> {
+        match &self.scope_stack.last().kind {
+            ScopeKind::Namespace(NamespaceScope {
+                param_name: Some(param_name),
+                ..
+            }) => Some(param_name),
+            _ => Some(self.current_namespace_decl_in_parent()?.namespace_name),
+        }
+    }
+
+    /// Whether `import X = N.Y` references a namespace with only types, or a member not exported by the namespace (e.g. an interface).
+    /// Only namespaces declared earlier in the file are known; anything else is assumed to be a value.
+    fn is_type_only_entity(
+        &self,
+        module_reference: &TSModuleReference<'_, impl AstAllocator>,
+    ) -> bool {
+        let (namespace_name, member_name) = match module_reference {
+            TSModuleReference::IdentifierReference(id) => (id.name.as_str(), None),
+            TSModuleReference::QualifiedName(qualified_name) => {
+                // `N.Y.Z` -> (`N`, `Y`)
+                let mut qualified_name = &**qualified_name;
+                while let TSTypeName::QualifiedName(left) = &qualified_name.left {
+                    qualified_name = left;
+                }
+                let TSTypeName::IdentifierReference(id) = &qualified_name.left else {
+                    return false;
+                };
+                (id.name.as_str(), Some(qualified_name.right.name.as_str()))
+            }
+            _ => return false,
+        };
+        for scope in self.scope_stack.iter().rev() {
+            if let Some(exported_names) =
+                scope.exported_names_by_namespace_names.get(namespace_name)
+            {
+                // Exported names are only collected when namespaces are transformed
+                return self.options.transform_namespaces
+                    && member_name
+                        .is_some_and(|member_name| !exported_names.contains(member_name));
+            }
+            if scope.type_only_namespace_names.contains(namespace_name) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// `var ` before enums and namespaces, or `let ` in nested blocks. Omitted if merged into an earlier declaration.
+    fn declaration_keyword(&self, is_merged: bool) -> &'static str {
+        if is_merged {
+            ""
+        } else if self.scope_stack.last().is_block {
+            "let "
+        } else {
+            "var "
+        }
+    }
+
+    /// Argument of the function generated for an enum or namespace in `pure_iifes` mode: `N||{}`,
+    /// or `{}` for `let` declarations, which can't be read before initialized.
+    fn pure_iife_argument(&self, name: &str, is_merged: bool) -> &'alloc str {
+        if !is_merged && self.scope_stack.last().is_block {
+            "{}"
+        } else {
+            format!(in &self.allocator, "{}||{{}}", name).into_bump_str()
+        }
+    }
+
+    /// `;(function(N){`, or `=/*#__PURE__*/(function(N){` in `pure_iifes` mode
+    fn namespace_head(&self, param_name: &str) -> &'alloc str {
+        if self.options.pure_iifes {
+            format!(in &self.allocator, "=/*#__PURE__*/(function({}){{", param_name)
+        } else {
+            format!(in &self.allocator, ";(function({}){{", param_name)
+        }
+        .into_bump_str()
+    }
+
+    /// Renames the parameter of the function generated for the namespace whose scope is the current one,
+    /// as a declaration in it shadows the namespace name. Like tsc, `N` is renamed to `N_1`.
+    fn rename_namespace_param(
+        &mut self,
+        namespace_name: &str,
+        index_of_patch_before_namespace_name: usize,
+    ) {
+        let allocator = self.allocator;
+        let (param_name, _) = self.unique_name(1, |suffix| {
+            format!(in allocator, "{}_{}", namespace_name, suffix).into_bump_str()
+        });
+        self.patches[index_of_patch_before_namespace_name + 1].replacement =
+            self.namespace_head(param_name);
+
+        let ScopeKind::Namespace(scope) = &mut self.scope_stack.last_mut().kind else {
+            unreachable!()
+        };
+        scope.param_name = Some(param_name);
+        // Dots in export assignments (`N.a=a;`) only follow the parameter.
+        let old_prefix = std::format!("{}.", namespace_name);
+        let new_prefix = std::format!("{}.", param_name);
+        for &pos in scope.export_positions.iter() {
+            let index = self.patches.partition_point(|patch| patch.span.start < pos);
+            for patch in self.patches[index..]
+                .iter_mut()
+                .take_while(|patch| patch.span.start == pos)
+            {
+                if patch.kind == PatchKind::NamespaceExport && patch.span.end == pos {
+                    patch.replacement = self
+                        .allocator
+                        .alloc_str(&patch.replacement.replace(&old_prefix, &new_prefix));
+                }
+            }
+        }
+        let pending_exports = scope.pending_exports.replace(&old_prefix, &new_prefix);
+        scope.pending_exports = String::from_str_in(&pending_exports, self.allocator);
+    }
+
+    /// Resolves references leaving a scope in a namespace.
+    /// References to exported mutable variables of the namespace, or to exports of its earlier blocks (`earlier_exports`),
+    /// are rewritten as its properties (`x` -> `N.x`), and the ones not declared in the scope are passed to the parent scope.
+    fn resolve_namespace_references(
+        &mut self,
+        kind: &ScopeKind<'alloc>,
+        bindings: &[(&'alloc str, Span)],
+        earlier_exports: &[&'alloc str],
+        references: &[NamespaceReference],
+    ) {
+        let (mutable_exports, param_name): (&[(&str, Span)], &str) = match kind {
+            ScopeKind::Namespace(NamespaceScope {
+                mutable_exports,
+                param_name,
+                ..
+            }) if !mutable_exports.is_empty() || !earlier_exports.is_empty() => (
+                &mutable_exports[..],
+                // The namespace declaration is in the parent scope, which is now the current one.
+                param_name
+                    .or_else(|| {
+                        Some(
+                            self.cur_scope()
+                                .current_namespace_decl
+                                .as_ref()?
+                                .namespace_name,
+                        )
+                    })
+                    .unwrap_or_default(),
+            ),
+            _ => (&[], ""),
+        };
+        for reference in references {
+            let name = &self.source[reference.span];
+            let is_export_reference = if let Some((_, decl_span)) =
+                mutable_exports.iter().find(|(id, _)| *id == name)
+            {
+                // References in the declaring statement (`export let a = 1, b = a`) run before the export assignments.
+                !(decl_span.start <= reference.span.start && reference.span.end <= decl_span.end)
+            } else if bindings.iter().any(|(binding, _)| *binding == name) {
+                false
+            } else if earlier_exports.iter().any(|id| *id == name) {
+                true
+            } else {
+                let parent = self.scope_stack.last_mut();
+                if parent.in_namespace {
+                    parent.unresolved_references.push(*reference);
+                }
+                false
+            };
+            // Already patched spans are erased types, inlined enum members,
+            // or duplicates from rewound parsing attempts.
+            if is_export_reference && !self.is_patched(reference.span) {
+                let replacement = if reference.is_shorthand {
+                    format!(in &self.allocator, "{0}:{1}.{0}", name, param_name)
+                } else {
+                    format!(in &self.allocator, "{1}.{0}", name, param_name)
+                };
+                self.patches.binary_search_insert(
+                    PatchKind::NamespaceExportReference,
+                    (reference.span, replacement.into_bump_str()),
+                );
+            }
+        }
+    }
+
+    fn mark_shorthand_reference(&mut self, span: Span) {
+        if let Some(reference) = self
+            .scope_stack
+            .last_mut()
+            .unresolved_references
+            .iter_mut()
+            .rev()
+            .find(|reference| reference.span == span)
+        {
+            reference.is_shorthand = true;
+        }
+    }
+
+    /// Name of the enum whose scope is the current one, which is also the parameter of the generated function.
+    fn current_enum_name(&self) -> &'alloc str {
+        self.scope_stack[self.scope_stack.len() - 2]
+            .current_enum_decl
+            .as_ref()
+            .expect("expect enum declaration to exist in the parent scope of the enum scope")
+            .enum_name
+    }
+
+    fn handle_enum_member_access<'ast, A: AstAllocator>(
+        &mut self,
+        span: Span,
+        object: &Expression<'ast, A>,
+        member_name: &str,
+    ) {
+        if !self.options.transform_enums {
+            return;
+        }
+        let value = match self.enum_member_access_value(object.span(), member_name) {
+            Some(value) => Some(value),
+            None => self.inline_const_enum_member(span, object, member_name),
+        };
+        if let Some(value) = value {
+            self.push_enum_expr_value(span, value);
+        }
+    }
+
+    /// Evaluates `Foo.A` or `Foo['A']` in the initializers of `enum Foo`.
+    fn enum_member_access_value(
+        &self,
+        object_span: Span,
+        member_name: &str,
+    ) -> Option<EnumValue<'alloc>> {
+        let ScopeKind::Enum(_) = &self.scope_stack.last().kind else {
+            return None;
+        };
+        let enum_name = self.scope_stack[self.scope_stack.len() - 2]
+            .current_enum_decl
+            .as_ref()?
+            .enum_name;
+        let object_name = &self.source[object_span];
+        if object_name != enum_name {
+            return None;
+        }
+        self.enum_member_value(member_name).flatten()
+    }
+
+    /// Moves the bindings of `var` declarations at `decl_span` from the current block to the nearest function or namespace scope,
+    /// so that they shadow names in the whole function (`function f() { { var x } return x }`).
+    fn hoist_var_bindings(&mut self, decl_span: Span) {
+        let bindings = &mut self.scope_stack.last_mut().bindings;
+        // Bindings in nested scopes of the declaration (`var f = function (a) {}`) have left with them.
+        let decl_bindings_start = bindings
+            .iter()
+            .rposition(|(_, span)| span.start < decl_span.start)
+            .map_or(0, |index| index + 1);
+        if decl_bindings_start == bindings.len() {
+            return;
+        }
+        let mut decl_bindings = Vec::new_in(self.allocator);
+        decl_bindings.extend(bindings.drain(decl_bindings_start..));
+        if let Some(var_scope) = self
+            .scope_stack
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.is_var_scope)
+        {
+            var_scope.bindings.extend(decl_bindings);
+        }
+    }
+
+    /// Whether `name` is declared in a scope nested in the scope at `scope_index`, shadowing a `const enum` declared there.
+    fn is_rebound_after_scope(&self, name: &str, scope_index: usize) -> bool {
+        self.scope_stack
+            .iter()
+            .skip(scope_index + 1)
+            .any(|scope| scope.bindings.iter().any(|(binding, _)| *binding == name))
+    }
+
+    /// Returns the first name generated by `name_with_suffix` from `first_suffix` on that isn't a word of the source,
+    /// so it can't clash with any binding or private name in the file, and its suffix.
+    fn unique_name(
+        &mut self,
+        first_suffix: u32,
+        name_with_suffix: impl Fn(u32) -> &'alloc str,
+    ) -> (&'alloc str, u32) {
+        let source = self.source;
+        let allocator = self.allocator;
+        let source_words = self.source_words.get_or_insert_with(|| {
+            let mut words: HashSet<_, DefaultHashBuilder, &Bump> = HashSet::new_in(allocator);
+            words.extend(
+                source
+                    .split(|ch: char| !(ch.is_alphanumeric() || matches!(ch, '_' | '$' | '#')))
+                    .filter(|word| !word.is_empty()),
+            );
+            words
+        });
+        (first_suffix..)
+            .map(|suffix| (name_with_suffix(suffix), suffix))
+            .find(|(name, _)| !source_words.contains(*name))
+            .unwrap()
+    }
+
+    /// Inlines `Foo.A`/`Foo["A"]` at `span` if `Foo` is a `const enum` declared in this file,
+    /// as the literal padded with whitespace. Returns the inlined value.
+    fn inline_const_enum_member<'ast, A: AstAllocator>(
+        &mut self,
+        span: Span,
+        object: &Expression<'ast, A>,
+        member_name: &str,
+    ) -> Option<EnumValue<'alloc>> {
+        let Expression::Identifier(enum_id) = object else {
+            return None;
+        };
+        let enum_name = enum_id.name.as_str();
+        let const_members =
+            self.scope_stack
+                .iter()
+                .enumerate()
+                .rev()
+                .find_map(|(scope_index, scope)| {
+                    Some((
+                        scope_index,
+                        scope.const_enum_members_by_enum_names.get(enum_name)?,
+                    ))
+                });
+        let value = if let Some((scope_index, const_members)) = const_members {
+            // `function f(Foo) { return Foo.A }`
+            if self.is_rebound_after_scope(enum_name, scope_index) {
+                return None;
+            }
+            const_members
+                .iter()
+                .rev()
+                .find(|(name, _)| *name == member_name)?
+                .1
+        } else {
+            let imported_const_enum = self.imported_const_enums.get(enum_name)?;
+            if self.is_rebound_after_scope(enum_name, 0) {
+                return None;
+            }
+            match imported_const_enum
+                .members
+                .get(member_name)?
+                .as_enum_value()
+            {
+                EnumValue::Number(number) => EnumValue::Number(number),
+                EnumValue::String(string) => EnumValue::String(self.allocator.alloc_str(string)),
+            }
+        };
+
+        let mut literal = String::from_str_in("", self.allocator);
+        value.write_js_literal(&mut literal).unwrap();
+        let needs_parens = match value {
+            EnumValue::Number(number) if number.is_sign_negative() && !number.is_nan() => true,
+            // `Foo.A.toString()` -> `100.toString()` without padding to end the integer literal
+            EnumValue::Number(_) => {
+                literal.bytes().all(|byte| byte.is_ascii_digit())
+                    && literal.len() >= span.size() as usize
+                    && self.source.as_bytes().get(span.end as usize) == Some(&b'.')
+            }
+            EnumValue::String(_) => false,
+        };
+        let replacement = if needs_parens {
+            format!(in &self.allocator, "({})", literal).into_bump_str()
+        } else {
+            literal.into_bump_str()
+        };
+        // The rest of the access is padded with whitespace. A `/* Foo.A */` comment like tsc's would never fit.
+        self.patches
+            .push_merging_tail(PatchKind::ConstEnumAccess, (span, replacement));
+        Some(value)
+    }
+
+    /// Erases imported `const enum`s whose references are all inlined or in erased code,
+    /// and import declarations whose specifiers are all erased.
+    fn erase_inlined_const_enum_imports(&mut self) {
+        let mut erased_specifier_counts = std::vec![0usize; self.const_enum_import_decls.len()];
+        let mut erased_specifiers = std::vec::Vec::new();
+        for imported_const_enum in self.imported_const_enums.values() {
+            if imported_const_enum
+                .reference_spans
+                .iter()
+                .all(|span| self.is_patched(*span))
+            {
+                erased_specifier_counts[imported_const_enum.decl_index] += 1;
+                erased_specifiers.push((
+                    imported_const_enum.decl_index,
+                    imported_const_enum.specifier_span,
+                ));
+            }
+        }
+        for (decl, erased_specifier_count) in self
+            .const_enum_import_decls
+            .iter()
+            .zip(&erased_specifier_counts)
+        {
+            if *erased_specifier_count == decl.value_specifier_count {
+                self.patches
+                    .binary_search_insert_merging(PatchKind::ConstEnumImport, decl.span);
+            }
+        }
+        for (decl_index, specifier_span) in erased_specifiers {
+            if erased_specifier_counts[decl_index]
+                < self.const_enum_import_decls[decl_index].value_specifier_count
+            {
+                self.patches
+                    .binary_search_insert(PatchKind::ConstEnumImport, specifier_span);
+            }
+        }
+    }
+
+    /// Reports references to erased `const enum`s that are not inlined or in erased code.
+    fn report_erased_const_enum_references(&mut self) {
+        let mut runtime_references = std::vec::Vec::new();
+        for (enum_name, reference_spans) in &self.erased_const_enum_references {
+            runtime_references.extend(
+                reference_spans
+                    .iter()
+                    .filter(|span| !self.is_patched(**span))
+                    .map(|span| (*span, *enum_name)),
+            );
+        }
+        runtime_references.sort_unstable_by_key(|(span, _)| span.start);
+        for (span, enum_name) in runtime_references {
+            self.diagnostics.push(
+                OxcDiagnostic::error(std::format!(
+                    "`const enum` `{}` is erased but referenced at runtime",
+                    enum_name
+                ))
+                .with_label(span)
+                .with_help("Only accesses of constant members can be inlined. Disable `erase_const_enums` to keep the declaration"),
+            );
+        }
+    }
+
+    /// Declares `__decorate`/`__param`/`__metadata` after the last top-level statement if they are used by lowered decorators,
+    /// unless they are declared or imported at the top level (e.g. from `tslib`).
+    fn declare_decorator_helpers(&mut self) {
+        let mut helpers = String::new_in(self.allocator);
+        if self.uses_decorate_helper && !self.declares_decorate_helper {
+            helpers.push_str(DECORATE_HELPER);
+        }
+        if self.uses_param_helper && !self.declares_param_helper {
+            helpers.push_str(PARAM_HELPER);
+        }
+        if self.uses_metadata_helper && !self.declares_metadata_helper {
+            helpers.push_str(METADATA_HELPER);
+        }
+        if !helpers.is_empty() {
+            self.patches.insert_at(
+                self.last_top_level_statement_end,
+                PatchKind::Decorator,
+                helpers.into_bump_str(),
+            );
+        }
+    }
+
+    /// Records a name bound at the top level, which might be a decorator helper declared or imported from `tslib`.
+    fn record_top_level_binding(&mut self, name: &str) {
+        match name {
+            "__decorate" => self.declares_decorate_helper = true,
+            "__param" => self.declares_param_helper = true,
+            "__metadata" => self.declares_metadata_helper = true,
+            _ => {}
+        }
+    }
+
+    /// Reports decorators that can't be lowered with `experimental_decorators`.
+    fn report_unsupported_decorator(&mut self, span: Span, message: &'static str) {
+        self.diagnostics.push(
+            OxcDiagnostic::error(message)
+                .with_label(span)
+                .with_help("The decorator is left untouched"),
+        );
+    }
+
+    /// Source of `span` with the patches in it applied, or `None` if it spans multiple lines and can't be copied.
+    fn patched_source(&self, span: Span) -> Option<&'alloc str> {
+        if contains_line_terminators(&self.source_bytes()[span.range()]) {
+            return None;
+        }
+        let start = self
+            .patches
+            .partition_point(|patch| patch.span.end <= span.start);
+        let end = self
+            .patches
+            .partition_point(|patch| patch.span.start < span.end);
+        let mut code = String::with_capacity_in(span.size() as usize, self.allocator);
+        let mut pos = span.start;
+        for patch in &self.patches[start..end] {
+            code.push_str(&self.source[Span::new(pos, patch.span.start)]);
+            code.push_str(patch.replacement);
+            pos = patch.span.end;
+        }
+        code.push_str(&self.source[Span::new(pos, span.end)]);
+        Some(code.into_bump_str())
+    }
+
+    /// Takes decorators (`@a`) handled in the current scope at or after `start`,
+    /// which are the decorators of the node being handled, as nodes are handled after their children.
+    fn take_decorators(&mut self, start: u32) -> &'alloc [Span] {
+        let decorators = &mut self.scope_stack.last_mut().decorators;
+        let index = decorators.partition_point(|decorator| decorator.start < start);
+        let taken = self.allocator.alloc_slice_copy(&decorators[index..]);
+        decorators.truncate(index);
+        taken
+    }
+
+    /// Keeps decorator expressions in place as array elements: `@a @b` -> `{head}a ,b{tail}`.
+    /// `decorators` must not be empty.
+    fn lower_decorators(&mut self, decorators: &[Span], head: &'alloc str, tail: &'alloc str) {
+        for (index, decorator) in decorators.iter().enumerate() {
+            let at_span = Span::new(decorator.start, decorator.start + 1);
+            debug_assert_eq!(&self.source[at_span], "@");
+            let replacement = if index == 0 { head } else { "," };
+            self.patches
+                .binary_search_insert(PatchKind::Decorator, (at_span, replacement));
+        }
+        let end = decorators.last().unwrap().end;
+        self.patches
+            .binary_search_insert(PatchKind::Decorator, (Span::new(end, end), tail));
+    }
+
+    /// Records a parameter of a method in the class scope, erasing its decorators, which are applied with the method's.
+    /// Unlike other decorators, they are copied, so the ones spanning multiple lines are reported and left untouched.
+    fn collect_method_parameter(
+        &mut self,
+        param: &FormalParameter<'_, impl AstAllocator>,
+        decorators: &[Span],
+    ) {
+        let mut decorator_exprs = Vec::new_in(self.allocator);
+        for decorator in decorators {
+            let Some(expr) = self.patched_source(Span::new(decorator.start + 1, decorator.end))
+            else {
+                self.report_unsupported_decorator(
+                    *decorator,
+                    "Parameter decorators spanning multiple lines are not supported",
+                );
+                continue;
+            };
+            decorator_exprs.push(expr.trim_start());
+            self.patches
+                .binary_search_insert_merging(PatchKind::Decorator, (*decorator, ""));
+        }
+        let type_metadata = self.type_annotation_metadata(
+            param
+                .pattern
+                .type_annotation
+                .as_ref()
+                .map(|annotation| annotation.type_annotation.span()),
+        );
+        let class_scope_index = self.scope_stack.len() - 2;
+        let ScopeKind::Class(ClassScope { method_params, .. }) =
+            &mut self.scope_stack[class_scope_index].kind
+        else {
+            return;
+        };
+        // Parameters of function types in the type annotation are handled before
+        while method_params
+            .last()
+            .is_some_and(|method_param| method_param.span.start >= param.span.start)
+        {
+            method_params.pop();
+        }
+        method_params.push(MethodParameter {
+            span: param.span,
+            decorators: decorator_exprs,
+            type_metadata,
+        });
+    }
+
+    /// Serialized type of a type annotation for decorator metadata, or `Object` without one
+    fn type_annotation_metadata(&self, type_span: Option<Span>) -> &'alloc str {
+        if !self.options.emit_decorator_metadata {
+            return "";
+        }
+        let Some(type_span) = type_span else {
+            return "Object";
+        };
+        let index = self
+            .type_metadata
+            .partition_point(|(span, _)| span.start < type_span.start);
+        match self.type_metadata.get(index) {
+            Some((span, metadata)) if *span == type_span => *metadata,
+            _ => "Object",
+        }
+    }
+
+    /// Serializes a type for decorator metadata from its syntax like tsc, given the serialized types directly in it.
+    /// Types that may not be values at runtime, like type references, are checked (`typeof A==="function"?A:Object`).
+    fn serialize_type(
+        &self,
+        ty: &TSType<'_, impl AstAllocator>,
+        children: &[(Span, &'alloc str)],
+    ) -> &'alloc str {
+        match ty {
+            TSType::TSNumberKeyword(_) => "Number",
+            TSType::TSStringKeyword(_) | TSType::TSTemplateLiteralType(_) => "String",
+            TSType::TSBooleanKeyword(_) | TSType::TSTypePredicate(_) => "Boolean",
+            TSType::TSBigIntKeyword(_) => "typeof BigInt===\"function\"?BigInt:Object",
+            TSType::TSSymbolKeyword(_) => "typeof Symbol===\"function\"?Symbol:Object",
+            TSType::TSVoidKeyword(_)
+            | TSType::TSUndefinedKeyword(_)
+            | TSType::TSNullKeyword(_)
+            | TSType::TSNeverKeyword(_) => "void 0",
+            TSType::TSFunctionType(_) | TSType::TSConstructorType(_) => "Function",
+            TSType::TSArrayType(_) | TSType::TSTupleType(_) => "Array",
+            TSType::TSLiteralType(literal_type) => match &literal_type.literal {
+                TSLiteral::StringLiteral(_) | TSLiteral::TemplateLiteral(_) => "String",
+                TSLiteral::NumericLiteral(_) | TSLiteral::UnaryExpression(_) => "Number",
+                TSLiteral::BooleanLiteral(_) => "Boolean",
+                TSLiteral::BigIntLiteral(_) => "typeof BigInt===\"function\"?BigInt:Object",
+                _ => "Object",
+            },
+            TSType::TSParenthesizedType(_) => {
+                children.first().map_or("Object", |(_, child)| *child)
+            }
+            TSType::TSTypeOperatorType(operator_type)
+                if matches!(operator_type.operator, TSTypeOperatorOperator::Readonly) =>
+            {
+                children.first().map_or("Object", |(_, child)| *child)
+            }
+            TSType::TSUnionType(_) | TSType::TSIntersectionType(_) => {
+                // `A | null` -> `A`, like tsc
+                let mut serialized = children
+                    .iter()
+                    .map(|(_, child)| *child)
+                    .filter(|child| *child != "void 0");
+                match serialized.next() {
+                    Some(first) if serialized.all(|child| child == first) => first,
+                    _ => "Object",
+                }
+            }
+            TSType::TSTypeReference(reference) => {
+                let mut names = Vec::new_in(self.allocator);
+                let mut type_name = &reference.type_name;
+                loop {
+                    match type_name {
+                        TSTypeName::IdentifierReference(id) => {
+                            names.push(&self.source[id.span]);
+                            break;
+                        }
+                        TSTypeName::QualifiedName(qualified_name) => {
+                            names.push(&self.source[qualified_name.right.span]);
+                            type_name = &qualified_name.left;
+                        }
+                        #[allow(unreachable_patterns)]
+                        _ => return "Object",
+                    }
+                }
+                names.reverse();
+                // `A.B.C` -> `typeof A!=="undefined"&&typeof A.B!=="undefined"&&typeof A.B.C==="function"?A.B.C:Object`
+                let mut code = String::new_in(self.allocator);
+                let mut path = String::new_in(self.allocator);
+                for (index, name) in names.iter().enumerate() {
+                    if index > 0 {
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                    if index < names.len() - 1 {
+                        code.write_fmt(format_args!("typeof {}!==\"undefined\"&&", path))
+                            .unwrap();
+                    }
+                }
+                code.write_fmt(format_args!("typeof {0}===\"function\"?{0}:Object", path))
+                    .unwrap();
+                code.into_bump_str()
+            }
+            _ => "Object",
+        }
+    }
+
+    /// The key argument of `__decorate` for a decorated member (`"a"`), or `None` if it's computed or private,
+    /// which is reported.
+    fn decorated_member_key(
+        &mut self,
+        key: &PropertyKey<'_, impl AstAllocator>,
+        computed: bool,
+    ) -> Option<&'alloc str> {
+        match key {
+            _ if computed => {}
+            PropertyKey::PrivateIdentifier(_) => {}
+            PropertyKey::StaticIdentifier(id) => {
+                return Some(
+                    format!(in &self.allocator, "\"{}\"", &self.source[id.span]).into_bump_str(),
+                );
+            }
+            // string and numeric literals
+            key => return Some(self.allocator.alloc_str(&self.source[key.span()])),
+        }
+        self.report_unsupported_decorator(
+            key.span(),
+            "Decorators on members with computed keys or private names are not supported",
+        );
+        None
+    }
+
+    /// Records a decorated member in the class scope. Its decorators are applied after the class in `handle_class`.
+    fn collect_decorated_member(
+        &mut self,
+        decorators: &'alloc [Span],
+        key: &'alloc str,
+        is_static: bool,
+        descriptor: &'static str,
+        extra_decorators: &'alloc str,
+    ) {
+        if let ScopeKind::Class(ClassScope {
+            decorated_members, ..
+        }) = &mut self.scope_stack.last_mut().kind
+        {
+            decorated_members.push(DecoratedMember {
+                decorators,
+                key,
+                is_static,
+                descriptor,
+                extra_decorators,
+            });
+        }
+    }
+
+    /// Writes `__decorate([a,__param(0,b)],A.prototype,"m",null);` applying the decorators of a member of class `class_name`,
+    /// and erases the decorators, which are copied like parameter decorators.
+    /// The ones spanning multiple lines are reported and left untouched.
+    fn lower_member_decorators(
+        &mut self,
+        code: &mut String<'alloc>,
+        class_name: &str,
+        member: &DecoratedMember<'alloc>,
+    ) {
+        let mut decorators = String::new_in(self.allocator);
+        let mut is_first = true;
+        for decorator in member.decorators {
+            let Some(expr) = self.patched_source(Span::new(decorator.start + 1, decorator.end))
+            else {
+                self.report_unsupported_decorator(
+                    *decorator,
+                    "Member decorators spanning multiple lines are not supported",
+                );
+                continue;
+            };
+            decorators.push(',');
+            decorators.push_str(expr.trim_start());
+            // `;` separates the member from the previous one: `a = 1\n@b *c() {}`
+            self.patches.binary_search_insert_merging(
+                PatchKind::Decorator,
+                (*decorator, if is_first { ";" } else { "" }),
+            );
+            is_first = false;
+        }
+        decorators.push_str(member.extra_decorators);
+        if decorators.is_empty() {
+            return;
+        }
+        let prototype = if member.is_static { "" } else { ".prototype" };
+        code.write_fmt(format_args!(
+            "__decorate([{}],{}{},{},{});",
+            &decorators[1..],
+            class_name,
+            prototype,
+            member.key,
+            member.descriptor
+        ))
+        .unwrap();
+    }
+
+    fn lower_method_decorators(
+        &mut self,
+        element: &MethodDefinition<'_, impl AstAllocator>,
+        decorators: &'alloc [Span],
+        is_stripped: bool,
+    ) {
+        let ScopeKind::Class(ClassScope { method_params, .. }) =
+            &mut self.scope_stack.last_mut().kind
+        else {
+            return;
+        };
+        let method_params = std::mem::replace(method_params, Vec::new_in(self.allocator));
+        if is_stripped {
+            return;
+        }
+        // Parameters of functions outside the parameter list (e.g. in decorators) might be collected as well
+        let params_span = element.value.params.span;
+        let mut param_decorators = String::new_in(self.allocator);
+        let mut param_types = String::new_in(self.allocator);
+        for (index, param) in method_params
+            .iter()
+            .filter(|param| {
+                param.span.start >= params_span.start && param.span.end <= params_span.end
+            })
+            .enumerate()
+        {
+            for expr in param.decorators.iter() {
+                param_decorators
+                    .write_fmt(format_args!(",__param({},{})", index, expr))
+                    .unwrap();
+            }
+            if index > 0 {
+                param_types.push(',');
+            }
+            param_types.push_str(param.type_metadata);
+        }
+        if !param_decorators.is_empty() {
+            self.uses_param_helper = true;
+        }
+        if element.kind == MethodDefinitionKind::Constructor {
+            let ScopeKind::Class(ClassScope {
+                constructor_param_decorators,
+                constructor_param_types,
+                ..
+            }) = &mut self.scope_stack.last_mut().kind
+            else {
+                unreachable!()
+            };
+            constructor_param_decorators.push_str(&param_decorators);
+            if self.options.emit_decorator_metadata {
+                *constructor_param_types = Some(param_types.into_bump_str());
+            }
+            return;
+        }
+        if decorators.is_empty() && param_decorators.is_empty() {
+            return;
+        }
+        let Some(key) = self.decorated_member_key(&element.key, element.computed) else {
+            return;
+        };
+        if self.options.emit_decorator_metadata {
+            let return_type = self.type_annotation_metadata(
+                element
+                    .value
+                    .return_type
+                    .as_ref()
+                    .map(|annotation| annotation.type_annotation.span()),
+            );
+            match element.kind {
+                MethodDefinitionKind::Get => param_decorators
+                    .write_fmt(format_args!(",__metadata(\"design:type\",{})", return_type)),
+                MethodDefinitionKind::Set => param_decorators.write_fmt(format_args!(
+                    ",__metadata(\"design:type\",{0}),__metadata(\"design:paramtypes\",[{0}])",
+                    // A setter has exactly one parameter
+                    param_types
+                )),
+                _ => param_decorators.write_fmt(format_args!(
+                    ",__metadata(\"design:type\",Function),__metadata(\"design:paramtypes\",[{}]),__metadata(\"design:returntype\",{})",
+                    param_types,
+                    match element.value.return_type {
+                        None if element.value.r#async => "Promise",
+                        None => "void 0",
+                        Some(_) => return_type,
+                    }
+                )),
+            }
+            .unwrap();
+            self.uses_metadata_helper = true;
+        }
+        self.collect_decorated_member(
+            decorators,
+            key,
+            element.r#static,
+            "null",
+            param_decorators.into_bump_str(),
+        );
+    }
+
+    /// `@a class A { @b m() {} }` -> `var A_decorators=()=>[a];class A { ;  m() {} }__decorate([b],A.prototype,"m",null);A=__decorate(A_decorators(),A);`.
+    /// Like tsc, decorators of instance members are applied first, then the ones of static members and the class.
+    /// Class decorator expressions are kept in place in a function, which is called after the class is defined.
+    fn lower_class_decorators(
+        &mut self,
+        class: &Class<'_, impl AstAllocator>,
+        decorators: &[Span],
+        constructor_param_decorators: &'alloc str,
+        constructor_param_types: Option<&'alloc str>,
+        decorated_members: &[DecoratedMember<'alloc>],
+    ) {
+        let (ClassType::ClassDeclaration, Some(id)) = (class.r#type, &class.id) else {
+            self.report_unsupported_decorator(
+                class.span,
+                "Decorators are only supported on named class declarations",
+            );
+            return;
+        };
+        let source = self.source;
+        let name = &source[id.span];
+        let body_span = class.body.span;
+        // `A=__decorate(...)` rebinds the class after its body is evaluated, so references to it there would see the undecorated class.
+        // tsc rewrites them to an alias (`let A = A_1 = class A {...}`), which can't be done in place.
+        if !(decorators.is_empty() && constructor_param_decorators.is_empty()) {
+            let contains = |span: Span, reference: Span| {
+                span.start <= reference.start && reference.end <= span.end
+            };
+            // Member decorators are applied after the class anyway
+            let self_reference = self
+                .class_body_references
+                .iter()
+                .find(|reference| {
+                    contains(body_span, **reference)
+                        && &source[**reference] == name
+                        && !decorated_members
+                            .iter()
+                            .flat_map(|member| member.decorators)
+                            .any(|decorator| contains(*decorator, **reference))
+                })
+                .copied();
+            if let Some(reference) = self_reference {
+                self.report_unsupported_decorator(
+                    reference,
+                    "Decorated classes referencing themselves in their bodies are not supported",
+                );
+                return;
+            }
+        }
+        let mut code = String::new_in(self.allocator);
+        let instance_members = decorated_members.iter().filter(|member| !member.is_static);
+        let static_members = decorated_members.iter().filter(|member| member.is_static);
+        for member in instance_members.chain(static_members) {
+            self.lower_member_decorators(&mut code, name, member);
+        }
+        if !(decorators.is_empty() && constructor_param_decorators.is_empty()) {
+            self.write_class_decorate(
+                &mut code,
+                name,
+                decorators,
+                constructor_param_decorators,
+                constructor_param_types,
+            );
+        }
+        if !code.is_empty() {
+            self.patches
+                .insert_at(class.span.end, PatchKind::Decorator, code.into_bump_str());
+            self.uses_decorate_helper = true;
+        }
+    }
+
+    /// Writes `A=__decorate(A_decorators(),A);` applying the decorators of class `name` and its constructor parameters.
+    fn write_class_decorate(
+        &mut self,
+        code: &mut String<'alloc>,
+        name: &str,
+        decorators: &[Span],
+        constructor_param_decorators: &'alloc str,
+        constructor_param_types: Option<&'alloc str>,
+    ) {
+        let mut constructor_decorators =
+            String::from_str_in(constructor_param_decorators, self.allocator);
+        if let Some(param_types) = constructor_param_types {
+            constructor_decorators
+                .write_fmt(format_args!(
+                    ",__metadata(\"design:paramtypes\",[{}])",
+                    param_types
+                ))
+                .unwrap();
+            self.uses_metadata_helper = true;
+        }
+        let decorators_code = if decorators.is_empty() {
+            format!(in &self.allocator, "[{}]", &constructor_decorators[1..])
+        } else {
+            let allocator = self.allocator;
+            let (decorators_name, _) = self.unique_name(0, |suffix| {
+                if suffix == 0 {
+                    format!(in allocator, "{}_decorators", name).into_bump_str()
+                } else {
+                    format!(in allocator, "{}_decorators_{}", name, suffix).into_bump_str()
+                }
+            });
+            let head = format!(in &self.allocator, "var {}=()=>[", decorators_name);
+            let tail = format!(in &self.allocator, "{}];", constructor_decorators);
+            self.lower_decorators(decorators, head.into_bump_str(), tail.into_bump_str());
+            self.first_class_decorator_start = Some(decorators[0].start);
+            format!(in &self.allocator, "{}()", decorators_name)
+        };
+        code.write_fmt(format_args!(
+            "{0}=__decorate({1},{0});",
+            name, decorators_code
+        ))
+        .unwrap();
+    }
+
+    /// `export @a class A {}` -> `var A_decorators=()=>[a];class A {}A=__decorate(A_decorators(),A);export{A};`,
+    /// as the declaration of the decorators can't be exported.
+    fn export_decorated_class(
+        &mut self,
+        export_start: u32,
+        class: &Class<'_, impl AstAllocator>,
+        is_default: bool,
+    ) {
+        let (Some(first_decorator_start), Some(id)) = (self.first_class_decorator_start, &class.id)
+        else {
+            return;
+        };
+        // `@a export class A {}`, or `export` removed in `handle_statement` in namespaces
+        if first_decorator_start < export_start
+            || (!is_default
+                && self.options.transform_namespaces
+                && matches!(self.scope_stack.last().kind, ScopeKind::Namespace(_)))
+        {
+            return;
+        }
+        self.patches.binary_search_insert(
+            PatchKind::Decorator,
+            (Span::new(export_start, first_decorator_start), ""),
+        );
+        let source = self.source;
+        let name = &source[id.span];
+        let export = if is_default {
+            format!(in &self.allocator, "export default {};", name)
+        } else {
+            format!(in &self.allocator, "export{{{}}};", name)
+        };
+        self.patches
+            .insert_at(class.span.end, PatchKind::Decorator, export.into_bump_str());
+    }
+
+    /// Whether `span` is inside a patch, which means it's either removed or rewritten.
+    fn is_patched(&self, span: Span) -> bool {
+        let index = self
+            .patches
+            .partition_point(|patch| patch.span.start <= span.start);
+        index > 0 && self.patches[index - 1].span.end >= span.end
+    }
 
     fn source_bytes(&self) -> &[u8] {
         self.source.as_bytes()
@@ -179,7 +1546,8 @@ impl<'source, 'alloc> StripHandler<'source, 'alloc> {
             return;
         };
         if last_patch.span == span && last_patch.replacement.is_empty() {
-            last_patch.replacement = ";"
+            last_patch.replacement = ";";
+            last_patch.kind = PatchKind::Asi;
         }
     }
 
@@ -211,6 +1579,7 @@ impl<'source, 'alloc> StripHandler<'source, 'alloc> {
         {
             first_modifier_patch.replacement =
                 format!(in self.allocator, ";{}", first_modifier_patch.replacement).into_bump_str();
+            first_modifier_patch.kind = PatchKind::Asi;
         }
     }
 
@@ -237,6 +1606,7 @@ impl<'source, 'alloc> StripHandler<'source, 'alloc> {
                         patch.replacement =
                             format!(in self.allocator, "{};", patch.replacement).into_bump_str();
                     }
+                    patch.kind = PatchKind::Asi;
                 }
             }
         }
@@ -383,11 +1753,14 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> ParserHandler<'ast, A>
         StripHandlerCheckpoint {
             patch_len: self.patches.len() as u32,
             scope_stack_len: self.scope_stack.len() as u32,
+            diagnostic_len: self.diagnostics.len() as u32,
         }
     }
 
     fn rewind(&mut self, checkpoint: Self::Checkpoint) {
         self.patches.truncate(checkpoint.patch_len as usize);
+        self.diagnostics
+            .truncate(checkpoint.diagnostic_len as usize);
 
         // TODO: implement NonEmptyStack::truncate
         // self.scope_stack.truncate(checkpoint.scope_stack_len);
@@ -405,28 +1778,104 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
                 parameter_prop_id_spans_in_first_constructor: Vec::new_in(self.allocator),
                 parameter_prop_id_spans: Vec::new_in(self.allocator),
                 parameter_prop_init_insert_start: None,
+                constructor_init: None,
+                field_inits: String::new_in(self.allocator),
+                static_field_inits: String::new_in(self.allocator),
+                next_field_init_suffix: 0,
+                method_params: Vec::new_in(self.allocator),
+                constructor_param_decorators: String::new_in(self.allocator),
+                constructor_param_types: None,
+                decorated_members: Vec::new_in(self.allocator),
             }),
             ScopeType::TSEnumDeclaration => ScopeKind::Enum(EnumScope {
                 member_names: Vec::new_in(self.allocator),
+                expr_values: Vec::new_in(self.allocator),
+                current_member_name_patch_index: None,
+                current_member_references_enum: false,
+                pending_member_code: String::new_in(self.allocator),
+                last_member_end_patch_index: 0,
             }),
             ScopeType::TSModuleDeclaration => ScopeKind::Namespace(NamespaceScope {
                 current_stmt_binding_identifiers: Vec::new_in(self.allocator),
                 // exported_identifiers: Vec::new_in(self.allocator),
                 is_ambient: true,
+                pending_exports: String::new_in(self.allocator),
+                pending_exports_pos: 0,
+                export_positions: Vec::new_in(self.allocator),
+                exported_names: Vec::new_in(self.allocator),
+                mutable_exports: Vec::new_in(self.allocator),
+                param_name: None,
             }),
+            // Track `super()` in methods (which might be constructors) even without parameter properties,
+            // as fields are assigned after it without `use_define_for_class_fields`.
+            ScopeType::Function
+                if !self.options.use_define_for_class_fields
+                    && matches!(self.scope_stack.last().kind, ScopeKind::Class(_)) =>
+            {
+                ScopeKind::FunctionWithParamProps(FunctionWithParamPropsScope {
+                    parameter_prop_id_spans: Vec::new_in(self.allocator),
+                    super_call_stmt_end: None,
+                    last_super_call_expr_span: None,
+                    prologue_scan_state: PrologueScanState::Init,
+                })
+            }
             _ => ScopeKind::Other,
         };
+        let in_namespace = self.options.transform_namespaces
+            && (self.scope_stack.last().in_namespace || matches!(kind, ScopeKind::Namespace(_)));
         self.scope_stack.push(Scope {
             last_statement: None,
             kind,
             current_enum_decl: None,
             member_identifiers_by_enum_names: HashMap::new_in(self.allocator),
+            const_enum_members_by_enum_names: HashMap::new_in(self.allocator),
+            class_and_function_names: HashSet::new_in(self.allocator),
+            exported_names_by_namespace_names: HashMap::new_in(self.allocator),
+            type_only_namespace_names: HashSet::new_in(self.allocator),
             current_namespace_decl: None,
+            in_namespace,
+            is_block: matches!(
+                T::SCOPE_TYPE,
+                ScopeType::BlockStatement | ScopeType::SwitchStatement | ScopeType::CatchClause
+            ),
+            is_var_scope: !matches!(
+                T::SCOPE_TYPE,
+                ScopeType::BlockStatement
+                    | ScopeType::SwitchStatement
+                    | ScopeType::CatchClause
+                    | ScopeType::ForStatement
+                    | ScopeType::ForInStatement
+                    | ScopeType::ForOfStatement
+            ),
+            bindings: Vec::new_in(self.allocator),
+            unresolved_references: Vec::new_in(self.allocator),
+            decorators: Vec::new_in(self.allocator),
         });
     }
 
     fn leave_scope(&mut self) {
         let scope = self.scope_stack.pop();
+        if scope.in_namespace {
+            // Exports of earlier blocks of the same namespace
+            let parent_scope = self.scope_stack.last();
+            let earlier_exports = match (&scope.kind, &parent_scope.current_namespace_decl) {
+                (ScopeKind::Namespace(_), Some(current_namespace_decl)) => parent_scope
+                    .exported_names_by_namespace_names
+                    .get(current_namespace_decl.namespace_name)
+                    .map(|exported_names| {
+                        let mut earlier_exports = Vec::new_in(self.allocator);
+                        earlier_exports.extend(exported_names.iter().copied());
+                        earlier_exports
+                    }),
+                _ => None,
+            };
+            self.resolve_namespace_references(
+                &scope.kind,
+                &scope.bindings,
+                earlier_exports.as_deref().unwrap_or_default(),
+                &scope.unresolved_references,
+            );
+        }
         match scope.kind {
             ScopeKind::FunctionWithParamProps(FunctionWithParamPropsScope {
                 parameter_prop_id_spans: parameter_prop_id_spans_under_function,
@@ -476,45 +1925,135 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
                         PrologueScanState::Init => None,
                     })
             }
-            ScopeKind::Enum(EnumScope { member_names }) => {
+            ScopeKind::Enum(EnumScope {
+                member_names,
+                pending_member_code,
+                last_member_end_patch_index,
+                ..
+            }) if self.options.transform_enums => {
                 let scope = self.scope_stack.last_mut();
-                let enum_name = scope.current_enum_decl.as_ref().unwrap().enum_name;
-                let member_identifiers = scope
-                    .member_identifiers_by_enum_names
-                    .entry(enum_name)
-                    .or_insert_with(|| HashSet::new_in(self.allocator));
-                member_identifiers.extend(member_names.into_iter().filter_map(|member_name| {
-                    if member_name.is_identifier {
-                        Some(member_name.value)
-                    } else {
-                        None
-                    }
-                }));
+                let current_enum_decl = scope.current_enum_decl.as_mut().unwrap();
+                if !pending_member_code.is_empty() {
+                    current_enum_decl.pending_member_code = Some(PendingEnumMemberCode {
+                        code: pending_member_code.into_bump_str(),
+                        last_member_end_patch_index,
+                    });
+                }
+                let enum_name = current_enum_decl.enum_name;
+                if current_enum_decl.is_const {
+                    self.const_enum_names.insert(enum_name);
+                    let const_members = scope
+                        .const_enum_members_by_enum_names
+                        .entry(enum_name)
+                        .or_insert_with(|| Vec::new_in(self.allocator));
+                    const_members.extend(member_names.iter().filter_map(|member_name| {
+                        let value = member_name.const_value?;
+                        if member_name.is_identifier {
+                            return Some((member_name.value, value));
+                        }
+                        // Quoted names without escapes: `'A-1'`
+                        let quoted = member_name.value;
+                        let unquoted = &quoted[1..quoted.len() - 1];
+                        if unquoted.contains('\\') {
+                            None
+                        } else {
+                            Some((unquoted, value))
+                        }
+                    }));
+                }
+                if !current_enum_decl.is_erased {
+                    let member_identifiers = scope
+                        .member_identifiers_by_enum_names
+                        .entry(enum_name)
+                        .or_insert_with(|| HashSet::new_in(self.allocator));
+                    member_identifiers.extend(member_names.into_iter().filter_map(|member_name| {
+                        if member_name.is_identifier {
+                            Some(member_name.value)
+                        } else {
+                            None
+                        }
+                    }));
+                }
             }
             ScopeKind::Namespace(namespace_scope) if !namespace_scope.is_ambient => {
                 let scope = self.scope_stack.last_mut();
-                scope.current_namespace_decl.as_mut().unwrap().is_ambient = false;
+                let current_namespace_decl = scope.current_namespace_decl.as_mut().unwrap();
+                current_namespace_decl.is_ambient = false;
+                current_namespace_decl.param_name = namespace_scope.param_name;
+                if !namespace_scope.pending_exports.is_empty() {
+                    current_namespace_decl.pending_exports = Some(PendingNamespaceExports {
+                        code: namespace_scope.pending_exports.into_bump_str(),
+                        pos: namespace_scope.pending_exports_pos,
+                    });
+                }
+                let namespace_name = current_namespace_decl.namespace_name;
+                scope
+                    .exported_names_by_namespace_names
+                    .entry(namespace_name)
+                    .or_insert_with(|| HashSet::new_in(self.allocator))
+                    .extend(namespace_scope.exported_names);
             }
             _ => {}
         }
     }
 
     fn handle_ts_export_assignment(&mut self, assignment: &TSExportAssignment<'ast, A>) {
-        self.patches.binary_search_insert((
-            Span::new(assignment.span.start, assignment.expression.span().start),
-            "module.exports = ",
-        ));
+        if self.report_non_erasable(assignment.span, "`export =` is not erasable") {
+            return;
+        }
+        self.patches.binary_search_insert(
+            PatchKind::ExportAssignment,
+            (
+                Span::new(assignment.span.start, assignment.expression.span().start),
+                match self.options.module_format {
+                    ModuleFormat::CommonJs => "module.exports = ",
+                    ModuleFormat::Esm => "export default ",
+                },
+            ),
+        );
     }
 
     fn handle_export_specifier(&mut self, specifier: &ExportSpecifier<'ast>) {
         if specifier.export_kind.is_type() {
-            self.patches.push(specifier.span);
+            self.patches
+                .push(PatchKind::TypeOnlyImportExport, specifier.span);
         }
     }
 
     fn handle_import_specifier(&mut self, specifier: &ImportSpecifier<'ast>) {
         if specifier.import_kind.is_type() {
-            self.patches.push(specifier.span);
+            self.patches
+                .push(PatchKind::TypeOnlyImportExport, specifier.span);
+        }
+        if self.options.imported_const_enums.is_some() {
+            self.current_import_specifiers.push(CurrentImportSpecifier {
+                span: specifier.span,
+                imported_and_local_names: Some((
+                    self.allocator.alloc_str(specifier.imported.name().as_str()),
+                    self.allocator.alloc_str(specifier.local.name.as_str()),
+                )),
+                is_type: specifier.import_kind.is_type(),
+            });
+        }
+    }
+
+    fn handle_import_default_specifier(&mut self, specifier: &ImportDefaultSpecifier<'ast>) {
+        if self.options.imported_const_enums.is_some() {
+            self.current_import_specifiers.push(CurrentImportSpecifier {
+                span: specifier.span,
+                imported_and_local_names: None,
+                is_type: false,
+            });
+        }
+    }
+
+    fn handle_import_namespace_specifier(&mut self, specifier: &ImportNamespaceSpecifier<'ast>) {
+        if self.options.imported_const_enums.is_some() {
+            self.current_import_specifiers.push(CurrentImportSpecifier {
+                span: specifier.span,
+                imported_and_local_names: None,
+                is_type: false,
+            });
         }
     }
 
@@ -523,52 +2062,94 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         decl: &TSNamespaceExportDeclaration<'ast>,
     ) {
         // export as namespace Foo;
-        self.patches.push(decl.span);
+        self.patches
+            .push(PatchKind::TypeOnlyImportExport, decl.span);
     }
 
     fn handle_export_named_declaration(&mut self, decl: &ExportNamedDeclaration<'ast, A>) {
         if decl.export_kind.is_type() {
-            self.patches.push_merging_tail(decl.span);
+            self.patches
+                .push_merging_tail(PatchKind::TypeOnlyImportExport, decl.span);
             return;
         }
         let Some(exported_decl) = &decl.declaration else {
             return;
         };
-        if matches!(self.patches.last(), Some(last_patch) if last_patch.replacement.is_empty() && last_patch.span == exported_decl.span())
+        if self.options.transform_enums
+            && self.scope_len() == 0
+            && matches!(exported_decl, Declaration::TSEnumDeclaration(_))
+        {
+            let current_enum_decl = self.scope_stack.last().current_enum_decl.as_ref().unwrap();
+            if current_enum_decl.is_const {
+                self.exported_const_enum_names
+                    .push(current_enum_decl.enum_name);
+            }
+        }
+        if let Some(last_patch) = self.patches.last().filter(|last_patch| {
+            last_patch.replacement.is_empty() && last_patch.span == exported_decl.span()
+        }) {
+            // `export interface A {}`/`export declare ...`
+            self.patches.push_merging_tail(last_patch.kind, decl.span);
+        } else if self.options.transform_enums
+            && matches!(exported_decl, Declaration::TSEnumDeclaration(_))
         {
-            self.patches.push_merging_tail(decl.span);
-        } else if matches!(exported_decl, Declaration::TSEnumDeclaration(_)) {
             let current_enum_decl = self.scope_stack.last().current_enum_decl.as_ref().unwrap();
             if current_enum_decl.is_secondary {
                 self.patches[current_enum_decl.index_of_patch_before_enum_name]
                     .span
                     .start = decl.span.start;
             }
+        } else if self.options.transform_namespaces
+            && matches!(exported_decl, Declaration::TSModuleDeclaration(_))
+        {
+            // The existing binding is already exported. In namespaces, `export` is removed in `handle_statement`.
+            let cur_scope = self.scope_stack.last();
+            if let (Some(current_namespace_decl), false) = (
+                &cur_scope.current_namespace_decl,
+                matches!(cur_scope.kind, ScopeKind::Namespace(_)),
+            ) {
+                if current_namespace_decl.is_merged {
+                    self.patches[current_namespace_decl.index_of_patch_before_namespace_name]
+                        .span
+                        .start = decl.span.start;
+                }
+            }
+        } else if let Declaration::ClassDeclaration(class) = exported_decl {
+            self.export_decorated_class(decl.span.start, class, false);
         }
     }
 
     fn handle_export_default_declaration(&mut self, decl: &ExportDefaultDeclaration<'ast, A>) {
-        let Some(last_patch) = self.patches.last() else {
-            return;
-        };
-        if last_patch.span == decl.declaration.span() {
-            self.patches.push_merging_tail(decl.span());
+        if let Some(last_patch) = self
+            .patches
+            .last()
+            .filter(|last_patch| last_patch.span == decl.declaration.span())
+        {
+            self.patches.push_merging_tail(last_patch.kind, decl.span());
+        } else if let ExportDefaultDeclarationKind::ClassDeclaration(class) = &decl.declaration {
+            self.export_decorated_class(decl.span().start, class, true);
         }
     }
 
     fn handle_export_all_declaration(&mut self, decl: &ExportAllDeclaration<'ast, A>) {
         if decl.export_kind.is_type() {
-            self.patches.push_merging_tail(decl.span);
+            self.patches
+                .push_merging_tail(PatchKind::TypeOnlyImportExport, decl.span);
         }
     }
 
     fn handle_ts_class_implements(&mut self, implements: &TSClassImplements<'ast, A>) {
-        self.patches.push_merging_tail(implements.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeAnnotation, implements.span);
     }
 
     fn handle_variable_declaration(&mut self, decl: &VariableDeclaration<'ast, A>) {
         if decl.declare {
-            self.patches.push_merging_tail(decl.span);
+            self.patches
+                .push_merging_tail(PatchKind::Ambient, decl.span);
+        }
+        if decl.kind == VariableDeclarationKind::Var && !self.scope_stack.last().is_var_scope {
+            self.hoist_var_bindings(decl.span);
         }
     }
 
@@ -576,7 +2157,8 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         &mut self,
         interface_decl: &TSInterfaceDeclaration<'ast, A>,
     ) {
-        self.patches.push_merging_tail(interface_decl.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeDeclaration, interface_decl.span);
     }
 
     fn handle_ts_module_declaration_name(&mut self, name: &TSModuleDeclarationName<'ast>) {
@@ -585,46 +2167,125 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         };
         let cur_scope = self.scope_stack.last_mut();
         let namespace_name = &self.source[name_identifier.span];
+        // `class A {}; namespace A {}` -> `class A {};  A;(function(A){...`
+        let is_merged = cur_scope.class_and_function_names.contains(namespace_name)
+            || cur_scope
+                .member_identifiers_by_enum_names
+                .contains_key(namespace_name)
+            || cur_scope
+                .exported_names_by_namespace_names
+                .contains_key(namespace_name);
 
         cur_scope.current_namespace_decl = Some(CurrentNamespaceDeclaration {
             namespace_name: self.allocator.alloc_str(namespace_name),
             // The span should cover the namespace/module token, but we don't know the start of it in `handle_ts_module_declaration_name`.
             // Store the index of the patch and change the span start later in `handle_ts_module_declaration``
             index_of_patch_before_namespace_name: self.patches.len(),
+            is_merged,
             is_ambient: true,
+            pending_exports: None,
+            param_name: None,
         });
 
-        self.patches.push((
-            name_identifier.span.start..name_identifier.span.start,
-            "var ",
-        ));
-        self.patches
-            .push(((name_identifier.span.end..name_identifier.span.end), format!(in &self.allocator, ";(function({}){{", namespace_name).into_bump_str()));
+        if !self.options.transform_namespaces {
+            return;
+        }
+        self.patches.push(
+            PatchKind::NamespaceWrapper,
+            (
+                name_identifier.span.start..name_identifier.span.start,
+                self.declaration_keyword(is_merged),
+            ),
+        );
+        self.patches.push(
+            PatchKind::NamespaceWrapper,
+            (
+                (name_identifier.span.end..name_identifier.span.end),
+                self.namespace_head(namespace_name),
+            ),
+        );
     }
 
     fn handle_binding_identifier(&mut self, id: &BindingIdentifier<'ast>) {
+        // Imports and variables. Names of functions and classes are bound in their own scopes.
+        if self.scope_len() == 0 {
+            self.record_top_level_binding(id.name.as_str());
+        }
+        let scope = self.scope_stack.last_mut();
+        // The name of an enum is handled in its own scope, which never contains accesses of other declarations of it.
+        let shadows_const_enum = !matches!(scope.kind, ScopeKind::Enum(_))
+            && self.const_enum_names.contains(id.name.as_str());
+        if scope.in_namespace || shadows_const_enum {
+            let name = &self.source[id.span];
+            scope
+                .bindings
+                .push((self.allocator.alloc_str(name), id.span));
+        }
         if let ScopeKind::Namespace(NamespaceScope {
             current_stmt_binding_identifiers,
+            param_name,
             ..
-        }) = &mut self.scope_stack.last_mut().kind
+        }) = &mut scope.kind
         {
             let id = &self.source[id.span];
             current_stmt_binding_identifiers.push(self.allocator.alloc_str(id));
+            if param_name.is_some() || !self.options.transform_namespaces {
+                return;
+            }
+            if let Some(&CurrentNamespaceDeclaration {
+                namespace_name,
+                index_of_patch_before_namespace_name,
+                ..
+            }) = self.current_namespace_decl_in_parent()
+            {
+                if namespace_name == id {
+                    self.rename_namespace_param(
+                        namespace_name,
+                        index_of_patch_before_namespace_name,
+                    );
+                }
+            }
         }
     }
 
     fn handle_ts_module_declaration(&mut self, decl: &TSModuleDeclaration<'ast, A>) {
         if decl.declare {
-            self.patches.push_merging_tail(decl.span);
+            self.patches
+                .push_merging_tail(PatchKind::Ambient, decl.span);
             return;
         }
 
+        let pending_exports = self
+            .scope_stack
+            .last_mut()
+            .current_namespace_decl
+            .as_mut()
+            .and_then(|current_namespace_decl| current_namespace_decl.pending_exports.take());
         let Some(current_namespace_decl) = &self.scope_stack.last().current_namespace_decl else {
             return;
         };
 
         if current_namespace_decl.is_ambient {
-            self.patches.push_merging_tail(decl.span);
+            // namespaces with only types
+            let namespace_name = current_namespace_decl.namespace_name;
+            self.scope_stack
+                .last_mut()
+                .type_only_namespace_names
+                .insert(namespace_name);
+            self.patches
+                .push_merging_tail(PatchKind::TypeDeclaration, decl.span);
+            return;
+        }
+        // report `namespace A.B {}` only once, at the outer `namespace A`
+        if decl.span.start != decl.id.span().start
+            && self.report_non_erasable(
+                Span::new(decl.span.start, decl.id.span().end),
+                "Instantiated namespaces are not erasable",
+            )
+        {
+            return;
+        }
+        if !self.options.transform_namespaces {
             return;
         }
 
@@ -640,19 +2301,43 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
                 .end = inner_module.span().start;
         }
 
+        let param_name = current_namespace_decl
+            .param_name
+            .unwrap_or(current_namespace_decl.namespace_name);
         // if the decl starts with the decl id, then we are at namespace B of `namespace A.B`
         let tail_replacement = if decl.span.start == decl.id.span().start {
-            let parent_namespace_name = self.scope_stack[self.scope_stack.len() - 2].current_namespace_decl.as_ref().expect("expect parent namespace A to exist while handling a subnamespace B (namespace A.B { .. }) ").namespace_name;
-            // }).call(B = A.B || A.B = {}, A.B);
-            format!(in &self.allocator, "}}).call({0}={1}.{0}||({1}.{0}={{}}),{1}.{0});", current_namespace_decl.namespace_name, parent_namespace_name)
+            // The parent namespace A is referenced by its parameter, as we are in its function.
+            let parent_param_name = self.current_namespace_param_name().expect("expect parent namespace A to exist while handling a subnamespace B (namespace A.B { .. }) ");
+            // })(B = A.B || A.B = {});
+            if self.options.pure_iifes {
+                // return B})(A.B||(A.B={}));
+                format!(in &self.allocator, "return {0}}})({1}.{2}||({1}.{2}={{}}));", param_name, parent_param_name, current_namespace_decl.namespace_name)
+            } else {
+                format!(in &self.allocator, "}})({0}={1}.{0}||({1}.{0}={{}}));", current_namespace_decl.namespace_name, parent_param_name)
+            }
+        } else if self.options.pure_iifes {
+            let argument = self.pure_iife_argument(
+                current_namespace_decl.namespace_name,
+                current_namespace_decl.is_merged,
+            );
+            format!(in &self.allocator, "return {1}}})({0});", argument, param_name)
         } else {
-            format!(in &self.allocator, "}}).call({0}||({0}={{}}),{0});", current_namespace_decl.namespace_name)
+            format!(in &self.allocator, "}})({0}||({0}={{}}));", current_namespace_decl.namespace_name)
         };
 
-        self.patches.push((
-            (decl.span.end..decl.span.end),
-            tail_replacement.into_bump_str(),
-        ));
+        // Declarations in the namespace body are block-scoped, so the exports can't go into the tail.
+        if let Some(PendingNamespaceExports { code, pos }) = pending_exports {
+            self.patches
+                .insert_at(pos, PatchKind::NamespaceExport, code);
+        }
+
+        self.patches.push(
+            PatchKind::NamespaceWrapper,
+            (
+                (decl.span.end..decl.span.end),
+                tail_replacement.into_bump_str(),
+            ),
+        );
 
         if let ScopeKind::Namespace(scope) = &mut self.scope_stack.last_mut().kind {
             scope.is_ambient = false;
@@ -660,20 +2345,45 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
     }
 
     fn handle_ts_enum_head(&mut self, enum_head: &TSEnumHead<'ast>) {
-        let cur_scope = self.scope_stack.last_mut();
+        if !enum_head.declare {
+            self.report_non_erasable(enum_head.span, "Enum declarations are not erasable");
+        }
+        if !self.options.transform_enums {
+            return;
+        }
         let enum_name = enum_head.id.name.as_str();
+        // Enums can be merged into earlier enums or namespaces of the same name
+        let is_secondary = {
+            let cur_scope = self.scope_stack.last();
+            cur_scope
+                .member_identifiers_by_enum_names
+                .contains_key(enum_name)
+                || cur_scope
+                    .exported_names_by_namespace_names
+                    .contains_key(enum_name)
+        };
+        let keyword = self.declaration_keyword(is_secondary);
+        let cur_scope = self.scope_stack.last_mut();
         let existing_member_identifiers = cur_scope.member_identifiers_by_enum_names.get(enum_name);
-        let is_secondary = existing_member_identifiers.is_some();
 
         cur_scope.current_enum_decl = Some(CurrentEnumDeclaration {
             enum_name: self.allocator.alloc_str(enum_name),
             index_of_patch_before_enum_name: self.patches.len(),
             is_secondary,
+            is_const: enum_head.r#const,
+            is_erased: enum_head.declare || (enum_head.r#const && self.options.erase_const_enums),
+            pending_member_code: None,
         });
+        if self.collect_const_enums_only {
+            return;
+        }
 
-        // `(const) enum A {` -> `var A;(function(A){var {Foo,Bar}=A;{`
-        self.patches
-            .push(((enum_head.span.start..enum_head.id.span.start), "var "));
+        // `(const) enum A {` -> `var A;(function(A){var {Foo,Bar}=A;{` (`let A` in nested blocks, no `var A` if merged)
+        // Members are assigned through the parameter `A` rather than `this`, so that `this` in initializers keeps its meaning.
+        self.patches.push(
+            PatchKind::EnumHead,
+            ((enum_head.span.start..enum_head.id.span.start), keyword),
+        );
         // self.patches.push_checking_line_terminator(Patch {
         //     span: (enum_head.span.start..enum_head.id.span.start).into(),
         //     replacement: if !is_secondary {
@@ -683,31 +2393,55 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         //     },
         // });
 
-        self.patches.push(Patch {
-            span: (enum_head.id.span.end..enum_head.id.span.end).into(),
-            replacement: {
-                let mut replacement = format!(in &self.allocator, ";(function({}){{", enum_name);
-                if let Some(existing_member_identifiers) = existing_member_identifiers {
-                    if !existing_member_identifiers.is_empty() {
-                        replacement.push_str("var {");
-                        for (index, member_id) in existing_member_identifiers.iter().enumerate() {
-                            replacement.push_str(member_id);
-                            if index < existing_member_identifiers.len() - 1 {
-                                replacement.push(',');
+        self.patches.push(
+            PatchKind::EnumHead,
+            Patch {
+                span: (enum_head.id.span.end..enum_head.id.span.end).into(),
+                replacement: {
+                    let mut replacement = if self.options.pure_iifes {
+                        // var A=/*#__PURE__*/(function(A){
+                        format!(in &self.allocator, "=/*#__PURE__*/(function({}){{", enum_name)
+                    } else {
+                        format!(in &self.allocator, ";(function({}){{", enum_name)
+                    };
+                    if let Some(existing_member_identifiers) = existing_member_identifiers {
+                        if !existing_member_identifiers.is_empty() {
+                            replacement.push_str("var {");
+                            for (index, member_id) in existing_member_identifiers.iter().enumerate()
+                            {
+                                replacement.push_str(member_id);
+                                if index < existing_member_identifiers.len() - 1 {
+                                    replacement.push(',');
+                                }
                             }
+                            replacement.push_str("}=");
+                            replacement.push_str(enum_name);
+                            replacement.push(';');
                         }
-                        replacement.push_str("}=");
-                        replacement.push_str(enum_name);
-                        replacement.push(';');
                     }
-                }
-                replacement.into_bump_str()
+                    replacement.into_bump_str()
+                },
+                ..Default::default()
             },
-        });
+        );
     }
 
     fn handle_ts_enum_member_name(&mut self, member_name: &TSEnumMemberName<'ast, A>) {
-        let ScopeKind::Enum(EnumScope { member_names }) = &mut self.scope_stack.last_mut().kind
+        if !self.options.transform_enums {
+            return;
+        }
+        let enum_name = self.current_enum_name();
+        let Scope {
+            kind:
+                ScopeKind::Enum(EnumScope {
+                    member_names,
+                    current_member_name_patch_index,
+                    ..
+                }),
+            in_namespace,
+            bindings,
+            ..
+        } = self.scope_stack.last_mut()
         else {
             if cfg!(debug_assertions) {
                 panic!("expect current scope to be Enum when TSEnumMemberName is encountered")
@@ -715,66 +2449,125 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
             return;
         };
         let span = member_name.span();
-        let name = match member_name {
+        let mut name = match member_name {
             TSEnumMemberName::StaticStringLiteral(string_literal) => {
                 let name = string_literal.value.as_str();
                 if is_identifier_name(name) {
                     EnumName {
                         value: self.allocator.alloc_str(name),
                         is_identifier: true,
+                        const_value: None,
                     }
                 } else {
                     EnumName {
                         value: self.allocator.alloc_str(&self.source[span]),
                         is_identifier: false,
+                        const_value: None,
                     }
                 }
             }
             TSEnumMemberName::StaticIdentifier(id) => EnumName {
                 value: self.allocator.alloc_str(id.name.as_str()),
                 is_identifier: true,
+                const_value: None,
             },
             _ => EnumName {
                 value: self.allocator.alloc_str(&self.source[span]),
                 is_identifier: false,
+                const_value: None,
             },
         };
+        if name.is_identifier && name.value == enum_name {
+            // A member named `A` in `enum A` can't be declared as a variable, which would shadow the parameter `A`.
+            name = EnumName {
+                value: format!(in &self.allocator, "\"{}\"", name.value).into_bump_str(),
+                is_identifier: false,
+                const_value: None,
+            };
+        }
+        if name.is_identifier && *in_namespace {
+            // Members are declared as variables in the enum function
+            bindings.push((name.value, span));
+        }
+        if self.collect_const_enums_only {
+            member_names.push(name);
+            return;
+        }
+        *current_member_name_patch_index = None;
         if !name.is_identifier {
-            self.patches.push_merging_tail((
-                span,
-                // this[this["C\n"] = 0] = "C\n";
-                // ^^^^^^^^^^^^^^^^
-                format!(in &self.allocator, "this[this[{}]", name.value).into_bump_str(),
-            ));
+            self.patches.push_merging_tail(
+                PatchKind::EnumMember,
+                (
+                    span,
+                    // A[A["C\n"] = 0] = "C\n";
+                    // ^^^^^^^^^^
+                    format!(in &self.allocator, "{0}[{0}[{1}]", enum_name, name.value)
+                        .into_bump_str(),
+                ),
+            );
+            *current_member_name_patch_index = Some(self.patches.len() - 1);
         } else if matches!(member_name, TSEnumMemberName::StaticStringLiteral(_)) {
             // "validIdentifier" to validIdentifier
-            self.patches.push_merging_tail((span, name.value));
+            self.patches
+                .push_merging_tail(PatchKind::EnumMember, (span, name.value));
         }
         member_names.push(name);
     }
 
     fn handle_ts_enum_member(&mut self, member: &TSEnumMember<'ast, A>) {
-        let ScopeKind::Enum(EnumScope { member_names }) = &self.scope_stack.last_mut().kind else {
+        if !self.options.transform_enums {
+            return;
+        }
+        let initializer_value = member
+            .initializer
+            .as_ref()
+            .map(|initializer| self.take_enum_expr_value(initializer.span()));
+        let enum_name = self.current_enum_name();
+        let ScopeKind::Enum(EnumScope {
+            member_names,
+            expr_values,
+            current_member_name_patch_index,
+            current_member_references_enum,
+            pending_member_code,
+            last_member_end_patch_index,
+        }) = &mut self.scope_stack.last_mut().kind
+        else {
             if cfg!(debug_assertions) {
                 panic!("expect current scope to be Enum when TSEnumMember is encountered")
             }
             return;
         };
-
-        let current_member_name = member_names.last().unwrap();
+        expr_values.clear();
+        let references_enum = std::mem::take(current_member_references_enum);
+
+        let last_member_name = member_names
+            .len()
+            .checked_sub(2)
+            .map(|last_member_idx| &member_names[last_member_idx]);
+        let const_value = match initializer_value {
+            Some(initializer_value) => initializer_value,
+            None => match last_member_name {
+                Some(EnumName {
+                    const_value: Some(EnumValue::Number(last_value)),
+                    ..
+                }) => Some(EnumValue::Number(last_value + 1.0)),
+                Some(_) => None,
+                None => Some(EnumValue::Number(0.0)),
+            },
+        };
         let mut replacement = String::from_str_in("", self.allocator);
 
         // init code
         if member.initializer.is_none() {
             replacement.push('=');
-            if let Some(last_member_idx) = member_names.len().checked_sub(2) {
-                let last_member_name = &member_names[last_member_idx];
+            if let Some(last_member_name) = last_member_name {
                 if last_member_name.is_identifier {
                     // = A
                     replacement.push_str(last_member_name.value)
                 } else {
-                    // = this['A\n']
-                    replacement.push_str("this[");
+                    // = A['B\n']
+                    replacement.push_str(enum_name);
+                    replacement.push('[');
                     replacement.push_str(last_member_name.value);
                     replacement.push(']');
                 }
@@ -785,73 +2578,302 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
             };
         };
 
+        // Like tsc, string members don't have reverse mappings,
+        // and members with non-constant initializers have reverse mappings only if they are not strings at runtime.
+        let is_string = matches!(const_value, Some(EnumValue::String(_)));
+        let is_non_constant = member.initializer.is_some() && const_value.is_none();
+
+        let end = member.span.end;
+        let mut span = Span::new(end, end);
+        let has_comma = self.source.as_bytes().get(end as usize).copied() == Some(b',');
+        if has_comma {
+            span.end += 1;
+        }
+
+        let current_member_name = member_names.last_mut().unwrap();
+        current_member_name.const_value = const_value;
+        if self.collect_const_enums_only {
+            return;
+        }
+        let current_member_name = &*current_member_name;
+
+        // The deferred code of previous members needs to run before this member if it's not on the same line,
+        // or if it may observe the enum object.
+        if !pending_member_code.is_empty() {
+            let last_member_end = self.patches[*last_member_end_patch_index].span.end;
+            if !current_member_name.is_identifier
+                || is_non_constant
+                || references_enum
+                || contains_line_terminators(
+                    &self.source.as_bytes()[last_member_end as usize..member.span.start as usize],
+                )
+            {
+                self.patches.append_statements(
+                    *last_member_end_patch_index,
+                    pending_member_code,
+                    self.allocator,
+                );
+                pending_member_code.clear();
+            }
+        }
+
         if current_member_name.is_identifier {
-            // A = 0;var A;this[this.A=A]="A";
-            //       ^^^^^^^^^^^^^^^^^^^^^^^^^
-            replacement
-                .write_fmt(format_args!(
-                    ";var {0};this[this.{0}={0}]='{0}';",
-                    current_member_name.value
-                ))
-                .unwrap();
+            if has_comma {
+                replacement.push(';');
+            }
+            if is_string {
+                // A = 'a';var A;E.A=A;
+                //         ^^^^^^^^^^^^
+                pending_member_code
+                    .write_fmt(format_args!(
+                        "var {0};{1}.{0}={0};",
+                        current_member_name.value, enum_name
+                    ))
+                    .unwrap();
+            } else if is_non_constant {
+                // A = f();var A;E.A=A;if(typeof A!=='string')E[A]='A';
+                //         ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+                pending_member_code
+                    .write_fmt(format_args!(
+                        "var {0};{1}.{0}={0};if(typeof {0}!=='string'){1}[{0}]='{0}';",
+                        current_member_name.value, enum_name
+                    ))
+                    .unwrap();
+            } else {
+                // A = 0;var A;E[E.A=A]="A";
+                //       ^^^^^^^^^^^^^^^^^^^
+                pending_member_code
+                    .write_fmt(format_args!(
+                        "var {0};{1}[{1}.{0}={0}]='{0}';",
+                        current_member_name.value, enum_name
+                    ))
+                    .unwrap();
+            }
+        } else if is_string || is_non_constant {
+            // E["C\n"] = 'c';
+            // ^^^^^^^^
+            let name_patch_index = current_member_name_patch_index.unwrap();
+            self.patches[name_patch_index].replacement =
+                format!(in &self.allocator, "{}[{}]", enum_name, current_member_name.value)
+                    .into_bump_str();
+            replacement.push(';');
+            if is_non_constant {
+                // E["C\n"] = f();if(typeof E["C\n"]!=='string')E[E["C\n"]]="C\n";
+                //               ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+                replacement
+                    .write_fmt(format_args!(
+                        "if(typeof {1}[{0}]!=='string'){1}[{1}[{0}]]={0};",
+                        current_member_name.value, enum_name
+                    ))
+                    .unwrap();
+            }
         } else {
-            // this[this["C\n"] = 0]="C\n";
-            //                     ^^^^^^^^^^
+            // E[E["C\n"] = 0]="C\n";
+            //               ^^^^^^^^^^
             replacement
                 .write_fmt(format_args!("]={};", current_member_name.value))
                 .unwrap();
         }
 
-        let end = member.span.end;
-        let mut span = Span::new(end, end);
-        if self.source.as_bytes().get(end as usize).copied() == Some(b',') {
-            span.end += 1;
-        }
-        self.patches.push((span, replacement.into_bump_str()));
+        // Pushed even if empty, as the place for the deferred code
+        self.patches
+            .push(PatchKind::EnumMember, (span, replacement.into_bump_str()));
+        *last_member_end_patch_index = self.patches.len() - 1;
     }
 
     fn handle_ts_enum_declaration(&mut self, enum_decl: &TSEnumDeclaration<'ast, A>) {
         if enum_decl.head.declare {
-            self.patches.push_merging_tail(enum_decl.span);
+            self.patches
+                .push_merging_tail(PatchKind::Ambient, enum_decl.span);
+            return;
+        }
+        if !self.options.transform_enums || self.collect_const_enums_only {
+            return;
+        }
+        if enum_decl.head.r#const && self.options.erase_const_enums {
+            // Member values are still recorded for inlining
+            self.patches
+                .push_merging_tail(PatchKind::TypeDeclaration, enum_decl.span);
+            let enum_name = self.allocator.alloc_str(enum_decl.head.id.name.as_str());
+            self.erased_const_enum_references
+                .entry(enum_name)
+                .or_insert_with(|| Vec::new_in(self.allocator));
             return;
         }
         let id = &self.source[enum_decl.head.id.span.range()];
 
-        self.patches.push(Patch {
-            span: (enum_decl.span.end..enum_decl.span.end).into(),
-            replacement: format!(in &self.allocator, "}}).call({0}||({0}={{}}),{0});", id)
+        let pending_member_code = self
+            .scope_stack
+            .last_mut()
+            .current_enum_decl
+            .as_mut()
+            .and_then(|current_enum_decl| current_enum_decl.pending_member_code.take());
+        let mut tail_prefix = "";
+        if let Some(PendingEnumMemberCode {
+            code,
+            last_member_end_patch_index,
+        }) = pending_member_code
+        {
+            let last_member_end = self.patches[last_member_end_patch_index].span.end;
+            if contains_line_terminators(
+                &self.source.as_bytes()[last_member_end as usize..enum_decl.span.end as usize],
+            ) {
+                self.patches
+                    .append_statements(last_member_end_patch_index, code, self.allocator);
+            } else {
+                // enum A { B = 1, C = B }var B;A[A.B=B]='B';var C;A[A.C=C]='C';})(A||(A={}));
+                //                        ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+                tail_prefix = code;
+            }
+        }
+
+        self.patches.push(
+            PatchKind::EnumTail,
+            (
+                (enum_decl.span.end..enum_decl.span.end),
+                if self.options.pure_iifes {
+                    let is_secondary = self
+                        .scope_stack
+                        .last()
+                        .current_enum_decl
+                        .as_ref()
+                        .is_some_and(|current_enum_decl| current_enum_decl.is_secondary);
+                    let argument = self.pure_iife_argument(id, is_secondary);
+                    format!(in &self.allocator, "{1}return {0}}})({2});", id, tail_prefix, argument)
+                } else {
+                    format!(in &self.allocator, "{1}}})({0}||({0}={{}}));", id, tail_prefix)
+                }
                 .into_bump_str(),
-        });
+            ),
+        );
     }
 
     fn handle_ts_import_equals_declaration(&mut self, decl: &TSImportEqualsDeclaration<'ast, A>) {
         if decl.import_kind.is_type() {
-            self.patches.push_merging_tail(decl.span);
+            self.patches
+                .push_merging_tail(PatchKind::TypeOnlyImportExport, decl.span);
+            return;
+        }
+        if self.is_type_only_entity(&decl.module_reference) {
+            self.patches
+                .push_merging_tail(PatchKind::TypeOnlyImportExport, decl.span);
+            return;
+        }
+        if self.report_non_erasable(decl.span, "`import =` declarations are not erasable") {
             return;
         }
         let const_span = Span::new(decl.span.start, decl.id.span.start);
-        self.patches.binary_search_insert((
-            const_span,
-            match decl.module_reference {
-                TSModuleReference::ExternalModuleReference(_) => "const ",
-                _ => "var ",
-            },
-        ));
+        match (&decl.module_reference, self.options.module_format) {
+            (TSModuleReference::ExternalModuleReference(module_ref), ModuleFormat::Esm) => {
+                // `import a = require('a')` -> `import a from 'a'`
+                self.patches.binary_search_insert(
+                    PatchKind::ImportEquals,
+                    (
+                        Span::new(decl.id.span.end, module_ref.expression.span.start),
+                        " from ",
+                    ),
+                );
+                self.patches.binary_search_insert(
+                    PatchKind::ImportEquals,
+                    Span::new(module_ref.expression.span.end, module_ref.span.end),
+                );
+            }
+            (TSModuleReference::ExternalModuleReference(_), ModuleFormat::CommonJs) => {
+                self.patches
+                    .binary_search_insert(PatchKind::ImportEquals, (const_span, "const "));
+            }
+            _ => {
+                // The span of `export import X = N.Y` starts at `export`, which is kept (`export var X = N.Y`),
+                // or removed in namespaces along with the assignment of the export (`N.X=X;`) in `handle_statement`.
+                let before_id = &self.source[Span::new(decl.span.start, decl.id.span.start)];
+                let import_start = match before_id.strip_prefix("export") {
+                    Some(after_export) => {
+                        decl.id.span.start - after_export.trim_start().len() as u32
+                    }
+                    None => decl.span.start,
+                };
+                self.patches.binary_search_insert(
+                    PatchKind::ImportEquals,
+                    (Span::new(import_start, decl.id.span.start), "var "),
+                );
+            }
+        }
     }
 
     fn handle_import_declaration(&mut self, decl: &ImportDeclaration<'ast, A>) {
+        let specifiers = std::mem::replace(
+            &mut self.current_import_specifiers,
+            Vec::new_in(self.allocator),
+        );
         if decl.import_kind.is_type() {
-            self.patches.push_merging_tail(decl.span);
+            self.patches
+                .push_merging_tail(PatchKind::TypeOnlyImportExport, decl.span);
+            return;
+        }
+        let Some(imported_const_enums) = self.options.imported_const_enums else {
+            return;
+        };
+        if !self.options.transform_enums {
+            return;
+        }
+        let Some(module) = imported_const_enums
+            .registry
+            .resolve(imported_const_enums.importer, decl.source.value.as_str())
+        else {
+            return;
+        };
+        let decl_index = self.const_enum_import_decls.len();
+        let mut has_const_enums = false;
+        for specifier in specifiers.iter().filter(|specifier| !specifier.is_type) {
+            let Some((imported_name, local_name)) = specifier.imported_and_local_names else {
+                continue;
+            };
+            let Some(members) = module.get(imported_name) else {
+                continue;
+            };
+            has_const_enums = true;
+            self.const_enum_names.insert(local_name);
+            self.imported_const_enums.insert(
+                local_name,
+                ImportedConstEnum {
+                    members,
+                    specifier_span: specifier.span,
+                    decl_index,
+                    reference_spans: Vec::new_in(self.allocator),
+                },
+            );
+        }
+        if has_const_enums {
+            self.const_enum_import_decls
+                .push(ConstEnumImportDeclaration {
+                    span: decl.span,
+                    value_specifier_count: specifiers
+                        .iter()
+                        .filter(|specifier| !specifier.is_type)
+                        .count(),
+                });
         }
     }
 
     fn handle_ts_type_alias_declaration(&mut self, decl: &TSTypeAliasDeclaration<'ast, A>) {
-        self.patches.push_merging_tail(decl.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeDeclaration, decl.span);
     }
 
     fn handle_function(&mut self, func: &Function<'ast, A>) {
+        if let (FunctionType::FunctionDeclaration, Some(id), 0) =
+            (func.r#type, &func.id, self.scope_len())
+        {
+            self.record_top_level_binding(id.name.as_str());
+        }
         if func.declare || func.body.is_none() {
-            self.patches.push_merging_tail(func.span);
+            self.patches
+                .push_merging_tail(PatchKind::Ambient, func.span);
+        } else if let (FunctionType::FunctionDeclaration, Some(id), true) =
+            (func.r#type, &func.id, self.options.transform_namespaces)
+        {
+            let name = self.allocator.alloc_str(id.name.as_str());
+            self.cur_scope_mut().class_and_function_names.insert(name);
         }
     }
 
@@ -881,11 +2903,21 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
             if last_patch.span.end == span.end && !last_patch.replacement.ends_with(";") {
                 last_patch.replacement =
                     format!(in &self.allocator, "{};", last_patch.replacement).into_bump_str();
+                last_patch.kind = PatchKind::Asi;
             }
         }
     }
 
     fn handle_statement(&mut self, stmt: &Statement<'ast, A>) {
+        if self.scope_len() == 0 {
+            self.last_top_level_statement_end = stmt.span().end;
+        }
+        let namespace_param_name = match self.scope_stack.last().kind {
+            ScopeKind::Namespace(_) if self.options.transform_namespaces => {
+                self.current_namespace_param_name().unwrap_or_default()
+            }
+            _ => "",
+        };
         let scope = self.scope_stack.last_mut();
         scope.current_enum_decl = None;
         scope.current_namespace_decl = None;
@@ -896,18 +2928,54 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
             ScopeKind::Namespace(NamespaceScope {
                 current_stmt_binding_identifiers,
                 is_ambient: ambient,
+                pending_exports,
+                pending_exports_pos,
+                export_positions,
+                exported_names,
+                mutable_exports,
                 ..
             }) => {
-                *ambient = *ambient
-                    && self
-                        .patches
-                        .last()
-                        .map(|last_patch| {
-                            matches!(last_patch.replacement, "" | ";")
-                                && last_patch.span == stmt.span()
-                        })
-                        .unwrap_or(false);
-                if let Statement::ExportNamedDeclaration(export_stmt) = stmt {
+                let is_erased = self
+                    .patches
+                    .last()
+                    .map(|last_patch| {
+                        matches!(last_patch.replacement, "" | ";") && last_patch.span == stmt.span()
+                    })
+                    .unwrap_or(false);
+                *ambient = *ambient && is_erased;
+                if !pending_exports.is_empty() {
+                    // Erased statements and (hoisted) function declarations can't observe the exports
+                    let is_inert = is_erased
+                        || match stmt {
+                            Statement::FunctionDeclaration(_) => true,
+                            Statement::ExportNamedDeclaration(export_stmt) => matches!(
+                                export_stmt.declaration,
+                                Some(Declaration::FunctionDeclaration(_))
+                            ),
+                            _ => false,
+                        };
+                    let stmt_span = stmt.span();
+                    if is_inert
+                        && !contains_line_terminators(
+                            &self.source.as_bytes()
+                                [*pending_exports_pos as usize..stmt_span.start as usize],
+                        )
+                    {
+                        *pending_exports_pos = stmt_span.end;
+                    } else {
+                        let pending_exports =
+                            std::mem::replace(pending_exports, String::new_in(self.allocator));
+                        self.patches.insert_at(
+                            *pending_exports_pos,
+                            PatchKind::NamespaceExport,
+                            pending_exports.into_bump_str(),
+                        );
+                        export_positions.push(*pending_exports_pos);
+                    }
+                }
+                if let (Statement::ExportNamedDeclaration(export_stmt), true) =
+                    (stmt, self.options.transform_namespaces)
+                {
                     // remove `export` of the export decl in namespaces, and add assignments after the decl...
                     let export_token_start = export_stmt.span().start;
                     // provided the whole decl is not already removed (e.g. export interface/export declare)
@@ -918,19 +2986,38 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
                             export_token_start + const { "export".len() as u32 },
                         );
                         debug_assert_eq!(&self.source[export_span], "export");
-                        self.patches.binary_search_insert((export_span, ""));
+                        self.patches
+                            .binary_search_insert(PatchKind::NamespaceExport, (export_span, ""));
 
-                        let mut assignments = String::new_in(self.allocator);
+                        // Deferred until the next statement or the end of the namespace,
+                        // to be inserted after the last token on the line.
                         let end = export_stmt.span().end;
+                        if pending_exports.is_empty() {
+                            *pending_exports_pos = end;
+                        }
                         if self.source.as_bytes()[end as usize - 1] != b';' {
-                            assignments.push(';');
+                            pending_exports.push(';');
+                        }
+                        if let Some(Declaration::VariableDeclaration(var_decl)) =
+                            &export_stmt.declaration
+                        {
+                            if matches!(
+                                var_decl.kind,
+                                VariableDeclarationKind::Let | VariableDeclarationKind::Var
+                            ) {
+                                mutable_exports.extend(
+                                    current_stmt_binding_identifiers
+                                        .iter()
+                                        .map(|id| (*id, export_stmt.span())),
+                                );
+                            }
                         }
+                        exported_names.extend(current_stmt_binding_identifiers.iter().copied());
                         for id in current_stmt_binding_identifiers.iter() {
-                            assignments
-                                .write_fmt(format_args!("this.{0}={0};", id))
+                            pending_exports
+                                .write_fmt(format_args!("{1}.{0}={0};", id, namespace_param_name))
                                 .unwrap();
                         }
-                        self.patches.push(((end..end), assignments.into_bump_str()));
                     }
                 }
                 current_stmt_binding_identifiers.clear();
@@ -947,6 +3034,144 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         };
     }
 
+    fn handle_string_literal(&mut self, lit: &StringLiteral<'ast>) {
+        // Checked before copying the value, which is only needed in enum member initializers
+        if !self.options.transform_enums || !self.in_enum_scope() {
+            return;
+        }
+        let value = self.allocator.alloc_str(lit.value.as_str());
+        self.push_enum_expr_value(lit.span, EnumValue::String(value));
+    }
+
+    fn handle_numeric_literal(&mut self, lit: &NumericLiteral<'ast>) {
+        if !self.options.transform_enums {
+            return;
+        }
+        self.push_enum_expr_value(lit.span, EnumValue::Number(lit.value));
+    }
+
+    fn handle_template_literal(&mut self, lit: &TemplateLiteral<'ast, A>) {
+        if !self.options.transform_enums || !self.in_enum_scope() {
+            return;
+        }
+        // Only templates without substitutions or escapes, whose values are the raw source.
+        let raw = &self.source[Span::new(lit.span.start + 1, lit.span.end - 1)];
+        if raw.contains(['\\', '\r']) || raw.contains("${") {
+            return;
+        }
+        let value = self.allocator.alloc_str(raw);
+        self.push_enum_expr_value(lit.span, EnumValue::String(value));
+    }
+
+    fn handle_identifier_reference(&mut self, id: &IdentifierReference<'ast>) {
+        if self.options.experimental_decorators
+            && self
+                .scope_stack
+                .iter()
+                .any(|scope| matches!(scope.kind, ScopeKind::Class(_)))
+        {
+            self.class_body_references.push(id.span);
+        }
+        let scope = self.scope_stack.last_mut();
+        if scope.in_namespace {
+            scope.unresolved_references.push(NamespaceReference {
+                span: id.span,
+                is_shorthand: false,
+            });
+        }
+        if !self.options.transform_enums {
+            return;
+        }
+        // A shadowing local doesn't reference the import, which can still be erased.
+        if self.imported_const_enums.contains_key(id.name.as_str())
+            && !self.is_rebound_after_scope(id.name.as_str(), 0)
+        {
+            let imported_const_enum = self.imported_const_enums.get_mut(id.name.as_str()).unwrap();
+            imported_const_enum.reference_spans.push(id.span);
+        }
+        if self
+            .erased_const_enum_references
+            .contains_key(id.name.as_str())
+        {
+            // Same for locals shadowing the erased enum: `function f(Foo) { return Foo.A }`
+            let declaring_scope_index = self.scope_stack.iter().rposition(|scope| {
+                scope
+                    .const_enum_members_by_enum_names
+                    .contains_key(id.name.as_str())
+            });
+            if declaring_scope_index.is_some_and(|scope_index| {
+                !self.is_rebound_after_scope(id.name.as_str(), scope_index)
+            }) {
+                let reference_spans = self
+                    .erased_const_enum_references
+                    .get_mut(id.name.as_str())
+                    .unwrap();
+                reference_spans.push(id.span);
+            }
+        }
+        if let ScopeKind::Enum(_) = self.scope_stack.last().kind {
+            let references_enum = self.scope_stack[self.scope_stack.len() - 2]
+                .current_enum_decl
+                .as_ref()
+                .is_some_and(|current_enum_decl| current_enum_decl.enum_name == id.name.as_str());
+            if let ScopeKind::Enum(EnumScope {
+                current_member_references_enum,
+                ..
+            }) = &mut self.scope_stack.last_mut().kind
+            {
+                *current_member_references_enum |= references_enum;
+            }
+        }
+        if let Some(Some(value)) = self.enum_member_value(id.name.as_str()) {
+            self.push_enum_expr_value(id.span, value);
+        }
+    }
+
+    fn handle_parenthesized_expression(&mut self, expr: &ParenthesizedExpression<'ast, A>) {
+        if let Some(value) = self.take_enum_expr_value(expr.expression.span()) {
+            self.push_enum_expr_value(expr.span, value);
+        }
+    }
+
+    fn handle_unary_expression(&mut self, expr: &UnaryExpression<'ast, A>) {
+        let Some(argument) = self.take_enum_expr_value(expr.argument.span()) else {
+            return;
+        };
+        if let Some(value) = EnumValue::eval_unary(expr.operator, argument) {
+            self.push_enum_expr_value(expr.span, value);
+        }
+    }
+
+    fn handle_binary_expression(&mut self, expr: &BinaryExpression<'ast, A>) {
+        let Some(right) = self.take_enum_expr_value(expr.right.span()) else {
+            return;
+        };
+        let Some(left) = self.take_enum_expr_value(expr.left.span()) else {
+            return;
+        };
+        let allocator = self.allocator;
+        let value = EnumValue::eval_binary(expr.operator, left, right, |left, right| {
+            format!(in allocator, "{}{}", left, right).into_bump_str()
+        });
+        if let Some(value) = value {
+            self.push_enum_expr_value(expr.span, value);
+        }
+    }
+
+    fn handle_static_member_expression(&mut self, expr: &StaticMemberExpression<'ast, A>) {
+        self.handle_enum_member_access(expr.span, &expr.object, expr.property.name.as_str());
+    }
+
+    fn handle_computed_member_expression(&mut self, expr: &ComputedMemberExpression<'ast, A>) {
+        let evaluated_member_name = self.take_enum_expr_value(expr.expression.span());
+        let member_name = match (&expr.expression, evaluated_member_name) {
+            (Expression::StringLiteral(lit), _) => lit.value.as_str(),
+            (_, Some(EnumValue::String(member_name))) => member_name,
+            _ => return,
+        };
+        self.handle_enum_member_access(expr.span, &expr.object, member_name);
+    }
+
     fn handle_call_expression(&mut self, call_expr: &CallExpression<'ast, A>) {
         if matches!(call_expr.callee, Expression::Super(_)) {
             if let ScopeKind::FunctionWithParamProps(FunctionWithParamPropsScope {
@@ -959,42 +3184,84 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         }
     }
 
+    fn handle_ts_type(&mut self, ty: &TSType<'ast, A>) {
+        if !self.options.emit_decorator_metadata {
+            return;
+        }
+        let span = ty.span();
+        let index = self
+            .type_metadata
+            .partition_point(|(child_span, _)| child_span.start < span.start);
+        let metadata = self.serialize_type(ty, &self.type_metadata[index..]);
+        self.type_metadata.truncate(index);
+        self.type_metadata.push((span, metadata));
+    }
+
     fn handle_ts_type_annotation(&mut self, it: &TSTypeAnnotation<'ast, A>) {
-        self.patches.push_merging_tail(it.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeAnnotation, it.span);
     }
 
     fn handle_ts_type_parameter_declaration(&mut self, it: &TSTypeParameterDeclaration<'ast, A>) {
-        self.patches.push_merging_tail(it.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeAnnotation, it.span);
     }
 
     fn handle_ts_type_parameter_instantiation(
         &mut self,
         it: &TSTypeParameterInstantiation<'ast, A>,
     ) {
-        self.patches.push_merging_tail(it.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeAnnotation, it.span);
     }
 
     fn handle_ts_as_expression(&mut self, it: &TSAsExpression<'ast, A>) {
-        self.patches
-            .push_merging_tail(it.expression.span().end..it.span.end);
+        self.patches.push_merging_tail(
+            PatchKind::AsOrSatisfies,
+            it.expression.span().end..it.span.end,
+        );
     }
 
     fn handle_ts_satisfies_expression(&mut self, it: &TSSatisfiesExpression<'ast, A>) {
-        self.patches
-            .push_merging_tail(it.expression.span().end..it.span.end);
+        self.patches.push_merging_tail(
+            PatchKind::AsOrSatisfies,
+            it.expression.span().end..it.span.end,
+        );
+    }
+
+    fn handle_decorator(&mut self, decorator: &Decorator<'ast, A>) {
+        self.cur_scope_mut().decorators.push(decorator.span);
     }
 
     fn handle_class_modifiers(&mut self, modifiers: &ClassModifiers) {
         if modifiers.r#abstract {
-            self.patches.push_merging_tail(modifiers.span);
+            self.patches
+                .push_merging_tail(PatchKind::Modifier, modifiers.span);
         }
     }
 
     fn handle_class_body(&mut self, class_body: &ClassBody<'ast, A>) {
+        if let ScopeKind::Class(ClassScope {
+            constructor_param_decorators,
+            constructor_param_types,
+            decorated_members,
+            ..
+        }) = &mut self.scope_stack.last_mut().kind
+        {
+            self.constructor_param_decorators =
+                std::mem::replace(constructor_param_decorators, String::new_in(self.allocator))
+                    .into_bump_str();
+            self.constructor_param_types = constructor_param_types.take();
+            self.decorated_members =
+                std::mem::replace(decorated_members, Vec::new_in(self.allocator)).into_bump_slice();
+        }
         let Scope {
             kind:
                 ScopeKind::Class(ClassScope {
                     parameter_prop_id_spans_in_first_constructor,
+                    constructor_init,
+                    field_inits,
+                    static_field_inits,
                     ..
                 }),
             ..
@@ -1004,6 +3271,52 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         };
         let class_body_start = class_body.span.start;
         debug_assert_eq!(self.source_bytes()[class_body_start as usize], b'{');
+
+        if !static_field_inits.is_empty() {
+            // Assigned after other static elements are defined, like `A.a=1;` after the class in tsc's output
+            let class_body_end = class_body.span.end - 1;
+            let static_block = format!(in &self.allocator, "static{{{}}}", static_field_inits);
+            self.patches.binary_search_insert(
+                PatchKind::ClassField,
+                (
+                    Span::new(class_body_end, class_body_end),
+                    static_block.into_bump_str(),
+                ),
+            );
+        }
+
+        match (constructor_init, field_inits.is_empty()) {
+            (Some((insert_span, prop_init_code)), true) => {
+                self.patches.binary_search_insert(
+                    PatchKind::ParameterPropertyInit,
+                    (*insert_span, *prop_init_code),
+                );
+            }
+            (Some((insert_span, prop_init_code)), false) => {
+                let init_code = format!(in &self.allocator, "{}{}", prop_init_code, field_inits);
+                self.patches.binary_search_insert(
+                    PatchKind::ClassField,
+                    (*insert_span, init_code.into_bump_str()),
+                );
+            }
+            (None, false) => {
+                // `super(...args)` is added in `handle_class` if the class has a super class
+                let constructor = format!(in &self.allocator, "constructor(){{{}}}", field_inits);
+                self.patches.binary_search_insert(
+                    PatchKind::ClassField,
+                    (
+                        Span::new(class_body_start + 1, class_body_start + 1),
+                        constructor.into_bump_str(),
+                    ),
+                );
+                return;
+            }
+            (None, true) => {}
+        }
+        // Fields of parameter properties are not declared with assign semantics
+        if !self.options.use_define_for_class_fields {
+            return;
+        }
         let mut prop_decls: String<'_> = String::with_capacity_in(
             parameter_prop_id_spans_in_first_constructor
                 .iter()
@@ -1015,20 +3328,82 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
             prop_decls.push_str(&self.source[*prop_id_span]);
             prop_decls.push(';');
         }
-        self.patches.binary_search_insert((
-            Span::new(class_body_start + 1, class_body_start + 1),
-            prop_decls.into_bump_str(),
-        ));
+        self.patches.binary_search_insert(
+            PatchKind::ParameterPropertyDeclaration,
+            (
+                Span::new(class_body_start + 1, class_body_start + 1),
+                prop_decls.into_bump_str(),
+            ),
+        );
     }
 
     fn handle_class(&mut self, it: &Class<'ast, A>) {
+        if it.super_class.is_some() && !self.options.use_define_for_class_fields {
+            // `constructor(){` -> `constructor(...args){super(...args);` for the constructor added in `handle_class_body`
+            let pos = it.body.span.start + 1;
+            let index = self.patches.partition_point(|patch| patch.span.start < pos);
+            if let Some(patch) = self.patches[index..]
+                .iter_mut()
+                .take_while(|patch| patch.span.start == pos)
+                .find(|patch| patch.kind == PatchKind::ClassField && patch.span.end == pos)
+            {
+                let replacement = patch.replacement;
+                let field_inits = &replacement["constructor(){".len()..];
+                patch.replacement = format!(
+                    in &self.allocator,
+                    "constructor(...args){{super(...args);{}",
+                    field_inits
+                )
+                .into_bump_str();
+            }
+        }
+        let constructor_param_decorators = std::mem::take(&mut self.constructor_param_decorators);
+        let constructor_param_types = self.constructor_param_types.take();
+        let decorated_members = std::mem::take(&mut self.decorated_members);
+        self.first_class_decorator_start = None;
+        if let (ClassType::ClassDeclaration, Some(id), 0) = (it.r#type, &it.id, self.scope_len()) {
+            self.record_top_level_binding(id.name.as_str());
+        }
+        // Decorators of a class declaration are all the remaining ones in the scope, as they might precede `export`
+        let decorators = self.take_decorators(match it.r#type {
+            ClassType::ClassDeclaration => 0,
+            ClassType::ClassExpression => it.span.start,
+        });
         if it.modifiers.is_some_and(|modifiers| modifiers.declare) {
-            self.patches.push_merging_tail(it.span);
+            self.patches.push_merging_tail(PatchKind::Ambient, it.span);
+            return;
+        }
+        if self.options.experimental_decorators
+            && !(decorators.is_empty()
+                && constructor_param_decorators.is_empty()
+                && decorated_members.is_empty())
+        {
+            self.lower_class_decorators(
+                it,
+                decorators,
+                constructor_param_decorators,
+                constructor_param_types,
+                decorated_members,
+            );
+        }
+        if let (ClassType::ClassDeclaration, Some(id), true) =
+            (it.r#type, &it.id, self.options.transform_namespaces)
+        {
+            let name = self.allocator.alloc_str(id.name.as_str());
+            self.cur_scope_mut().class_and_function_names.insert(name);
+        }
+        if !self
+            .scope_stack
+            .iter()
+            .any(|scope| matches!(scope.kind, ScopeKind::Class(_)))
+        {
+            self.class_body_references.clear();
         }
     }
 
     fn handle_ts_this_parameter(&mut self, it: &TSThisParameter<'ast, A>) {
-        self.patches.push_merging_tail(it.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeAnnotation, it.span);
     }
 
     fn handle_ts_function_type(&mut self, ts_func_type: &TSFunctionType<'ast, A>) {
@@ -1079,13 +3454,13 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
             ];
             for ts_modifier in TS_MODIFIERS {
                 if modifiers_source[start..].starts_with(ts_modifier) {
-                    self.patches.push(Patch {
-                        span: Span::new(
+                    self.patches.push(
+                        PatchKind::Modifier,
+                        Span::new(
                             modifiers.span.start + start as u32,
                             modifiers.span.start + (start + ts_modifier.len()) as u32,
                         ),
-                        replacement: "",
-                    });
+                    );
                     current_element_first_modifier_patch_index
                         .get_or_insert(self.patches.len() - 1);
                     start += ts_modifier.len();
@@ -1097,24 +3472,35 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
     }
 
     fn handle_ts_definite_mark(&mut self, mark: &TSDefiniteMark) {
-        self.patches.push_merging_tail(mark.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeAnnotation, mark.span);
     }
 
     fn handle_ts_optional_mark(&mut self, mark: &TSOptionalMark) {
-        self.patches.push_merging_tail(mark.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeAnnotation, mark.span);
     }
 
     fn handle_method_definition(&mut self, element: &MethodDefinition<'ast, A>) {
-        if matches!(self.patches.last(), Some(last_patch) if last_patch.span == element.value.span() && last_patch.replacement.is_empty())
-        {
+        let decorators = self.take_decorators(element.span.start);
+        let mut is_stripped = false;
+        if let Some(last_patch) = self.patches.last().filter(|last_patch| {
+            last_patch.span == element.value.span() && last_patch.replacement.is_empty()
+        }) {
             // if the function part is stripped (declare or empty body), strip the whole method
-            self.patches.push_merging_tail(element.span);
+            self.patches
+                .push_merging_tail(last_patch.kind, element.span);
+            is_stripped = true;
+        }
+        if self.options.experimental_decorators {
+            self.lower_method_decorators(element, decorators, is_stripped);
         }
 
         if let ScopeKind::Class(ClassScope {
             parameter_prop_id_spans_in_first_constructor,
             parameter_prop_id_spans,
             parameter_prop_init_insert_start,
+            constructor_init,
             ..
         }) = &mut self.scope_stack.last_mut().kind
         {
@@ -1166,10 +3552,7 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
                     prop_init_code.push_str(ident);
                     prop_init_code.push_str(";");
                 }
-                let prop_init_code = prop_init_code.into_bump_str();
-
-                self.patches
-                    .binary_search_insert((insert_span, prop_init_code));
+                *constructor_init = Some((insert_span, prop_init_code.into_bump_str()));
             } else {
                 // clear param prop state in class scope if the method isn't constructor or the constructor body is empty (someMethod(public a)),
                 // to avoid emiting field declarations.
@@ -1184,40 +3567,166 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
     }
 
     fn handle_property_definition(&mut self, element: &PropertyDefinition<'ast, A>) {
+        let decorators = self.take_decorators(element.span.start);
         if element
             .modifiers
             .is_some_and(|modifiers| modifiers.declare || modifiers.r#abstract)
         {
-            self.patches.push_merging_tail(element.span);
+            self.patches
+                .push_merging_tail(PatchKind::Ambient, element.span);
+            return;
+        }
+        if self.options.experimental_decorators && !decorators.is_empty() {
+            if let Some(key) = self.decorated_member_key(&element.key, element.computed) {
+                let metadata = if self.options.emit_decorator_metadata {
+                    self.uses_metadata_helper = true;
+                    let type_metadata = self.type_annotation_metadata(
+                        element
+                            .type_annotation
+                            .as_ref()
+                            .map(|annotation| annotation.type_annotation.span()),
+                    );
+                    format!(in &self.allocator, ",__metadata(\"design:type\",{})", type_metadata)
+                        .into_bump_str()
+                } else {
+                    ""
+                };
+                self.collect_decorated_member(
+                    decorators,
+                    key,
+                    element.r#static,
+                    "void 0",
+                    metadata,
+                );
+            }
+        }
+        // Private, computed and decorated fields keep define semantics
+        if self.options.use_define_for_class_fields
+            || element.computed
+            || matches!(element.key, PropertyKey::PrivateIdentifier(_))
+            || !decorators.is_empty()
+        {
+            return;
+        }
+        let Some(value) = &element.value else {
+            // `a: string;` declares nothing with assign semantics
+            self.patches
+                .push_merging_tail(PatchKind::ClassField, element.span);
+            return;
+        };
+        // `a: T = 1` -> `#init_0(){return(1)}`, assigned in the constructor (`this.a=this.#init_0();`),
+        // or in a static block at the end of the class body for static fields (`static{this.a=this.#init_0();}`)
+        let key_span = element.key.span();
+        let eq_search_start = element
+            .type_annotation
+            .as_ref()
+            .map_or(key_span.end, |type_annotation| type_annotation.span.end);
+        let Some(eq_offset) = self.source[Span::new(eq_search_start, value.span().start)].find('=')
+        else {
+            return;
+        };
+        let eq_start = eq_search_start + eq_offset as u32;
+
+        let ScopeKind::Class(ClassScope {
+            next_field_init_suffix,
+            ..
+        }) = &self.scope_stack.last().kind
+        else {
+            return;
+        };
+        let first_suffix = *next_field_init_suffix;
+        let allocator = self.allocator;
+        let (method_name, suffix) = self.unique_name(first_suffix, |suffix| {
+            format!(in allocator, "#init_{}", suffix).into_bump_str()
+        });
+        let ScopeKind::Class(ClassScope {
+            field_inits,
+            static_field_inits,
+            next_field_init_suffix,
+            ..
+        }) = &mut self.scope_stack.last_mut().kind
+        else {
+            unreachable!()
+        };
+        *next_field_init_suffix = suffix + 1;
+        let field_inits = if element.r#static {
+            static_field_inits
+        } else {
+            field_inits
+        };
+        let key = &self.source[key_span];
+        if let PropertyKey::StaticIdentifier(_) = &element.key {
+            field_inits
+                .write_fmt(format_args!("this.{}=this.{}();", key, method_name))
+                .unwrap();
+        } else {
+            field_inits
+                .write_fmt(format_args!("this[{}]=this.{}();", key, method_name))
+                .unwrap();
         }
+
+        self.patches.binary_search_insert(
+            PatchKind::ClassField,
+            (
+                key_span,
+                format!(in &self.allocator, "{}(){{", method_name).into_bump_str(),
+            ),
+        );
+        self.patches.binary_search_insert(
+            PatchKind::ClassField,
+            (Span::new(eq_start, eq_start + 1), "return("),
+        );
+        let value_end = value.span().end;
+        self.patches
+            .push(PatchKind::ClassField, ((value_end..value_end), ")}"));
     }
 
     fn handle_accessor_property(&mut self, element: &AccessorProperty<'ast, A>) {
+        let decorators = self.take_decorators(element.span.start);
         if element
             .modifiers
             .is_some_and(|modifiers| modifiers.declare || modifiers.r#abstract)
         {
-            self.patches.push_merging_tail(element.span);
+            self.patches
+                .push_merging_tail(PatchKind::Ambient, element.span);
+            return;
+        }
+        if self.options.experimental_decorators && !decorators.is_empty() {
+            self.report_unsupported_decorator(
+                decorators[0],
+                "Decorators are not supported on `accessor` fields",
+            );
         }
     }
 
     fn handle_ts_index_signature(&mut self, element: &TSIndexSignature<'ast, A>) {
-        self.patches.push_merging_tail(element.span);
+        self.patches
+            .push_merging_tail(PatchKind::TypeDeclaration, element.span);
     }
 
     fn handle_object_property(&mut self, prop: &ObjectProperty<'ast, A>) {
+        if prop.shorthand {
+            self.mark_shorthand_reference(prop.value.span());
+        }
         if prop.method {
             if let (Some(patch), Expression::FunctionExpression(function_value)) =
                 (self.patches.last(), &prop.value)
             {
                 if patch.span == function_value.span() {
-                    self.patches.push_merging_tail(prop.span);
+                    self.patches.push_merging_tail(patch.kind, prop.span);
                 }
             }
         }
         // if prop.method && matches(&prop.value, Expression::
     }
 
+    fn handle_assignment_target_property_identifier(
+        &mut self,
+        it: &AssignmentTargetPropertyIdentifier<'ast, A>,
+    ) {
+        self.mark_shorthand_reference(it.binding.span);
+    }
+
     fn handle_arrow_function_expression(&mut self, arrow_func: &ArrowFunctionExpression<'ast, A>) {
         /*
            `<T>
@@ -1236,6 +3745,7 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
             debug_assert_eq!(type_param_strip_patch.replacement, "");
 
             type_param_strip_patch.replacement = "(";
+            type_param_strip_patch.kind = PatchKind::ArrowFunctionParens;
             type_param_strip_patch.span.end = arrow_func.params.span().start + 1;
         }
         /*
@@ -1263,10 +3773,8 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
                     Span::new(strip_patch.span.end, strip_patch.span.end + 1);
                 self.patches.insert(
                     return_type_strip_patch_index + 1,
-                    Patch {
-                        span: closing_parenthesis_span,
-                        replacement: ")",
-                    },
+                    PatchKind::ArrowFunctionParens,
+                    (closing_parenthesis_span, ")"),
                 );
             } else {
                 #[cfg(debug_assertions)]
@@ -1285,20 +3793,21 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         assertion_annotaion: &TSTypeAssertionAnnotation<'ast, A>,
     ) {
         self.patches
-            .push_merging_tail((assertion_annotaion.span, "("));
+            .push_merging_tail(PatchKind::TypeAssertion, (assertion_annotaion.span, "("));
     }
 
     fn handle_ts_type_assertion(&mut self, type_assertion: &TSTypeAssertion<'ast, A>) {
-        self.patches.push(Patch {
-            span: (type_assertion.span.end..type_assertion.span.end).into(),
-            replacement: ")",
-        });
+        self.patches.push(
+            PatchKind::TypeAssertion,
+            ((type_assertion.span.end..type_assertion.span.end), ")"),
+        );
     }
 
     fn handle_if_statement(&mut self, if_stmt: &IfStatement<'ast, A>) {
         if let (Some(alternate), Some(last_patch)) = (&if_stmt.alternate, self.patches.last_mut()) {
             if last_patch.span == alternate.span() && last_patch.replacement.is_empty() {
-                last_patch.replacement = ";"
+                last_patch.replacement = ";";
+                last_patch.kind = PatchKind::Asi;
             }
         }
         let consequent_span = if_stmt.consequent.span();
@@ -1318,7 +3827,8 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
         if possible_strip_patch_of_consequent.span == if_stmt.consequent.span()
             && possible_strip_patch_of_consequent.replacement.is_empty()
         {
-            possible_strip_patch_of_consequent.replacement = ";"
+            possible_strip_patch_of_consequent.replacement = ";";
+            possible_strip_patch_of_consequent.kind = PatchKind::Asi;
         }
     }
 
@@ -1349,17 +3859,36 @@ impl<'source, 'alloc, 'ast, A: AstAllocator> AstHandler<'ast, A> for StripHandle
 
     #[inline]
     fn handle_formal_parameter_modifiers(&mut self, modifiers: &FormalParameterModifiers) {
-        self.patches.push_merging_tail(modifiers.span);
+        if !self.options.transform_parameter_properties {
+            return;
+        }
+        self.patches
+            .push_merging_tail(PatchKind::Modifier, modifiers.span);
     }
 
     #[inline]
     fn handle_formal_parameter(&mut self, param: &FormalParameter<'ast, A>) {
+        let decorators = self.take_decorators(param.span.start);
+        if self.options.experimental_decorators
+            && self.scope_len() > 0
+            && matches!(
+                self.scope_stack[self.scope_stack.len() - 2].kind,
+                ScopeKind::Class(_)
+            )
+        {
+            self.collect_method_parameter(param, decorators);
+        }
         let Some(modifiers) = &param.modifiers else {
             return;
         };
         if !(modifiers.r#override || modifiers.readonly || modifiers.accessibility.is_some()) {
             return;
         }
+        if self.report_non_erasable(param.span, "Parameter properties are not erasable")
+            || !self.options.transform_parameter_properties
+        {
+            return;
+        }
         self.handle_property_paramemter(param);
     }
 }
@@ -1,35 +1,396 @@
+#[cfg(feature = "rayon")]
+mod batch;
+mod const_enum;
+mod edit;
+mod enum_value;
+mod error;
 mod handler;
+mod options;
 mod patch;
+mod source_map;
 mod string_buf;
+#[cfg(test)]
+mod test_utils;
+mod utf16;
 
 #[doc(hidden)]
 pub mod line_term;
+pub mod lsp;
 mod patch_builder;
 
+use std::{io, ops::Range};
+
+#[cfg(feature = "rayon")]
+pub use batch::transpile_many;
+pub use const_enum::{ConstEnumRegistry, ImportedConstEnums};
+pub use edit::Edit;
+pub use error::{ErrorInfo, ErrorLabel, TranspileError};
 use handler::StripHandler;
+pub use options::{ModuleFormat, TranspileOptions};
 pub use oxc_allocator::Allocator;
 pub use oxc_allocator::String;
+use oxc_allocator::Vec;
 pub use oxc_diagnostics;
+use oxc_diagnostics::OxcDiagnostic;
 use oxc_parser::{ParseOptions, Parser};
 pub use oxc_span::SourceType;
+use patch::validate_patches;
+use source_map::shifted_line_ranges;
+pub use source_map::SourceMap;
 // expose for bench
 #[doc(hidden)]
 pub use oxc_span::ast_alloc::VoidAllocator;
+pub use patch::PatchKind;
 #[doc(hidden)]
-pub use patch::{apply_patches, Patch};
+pub use patch::{apply_patches, write_patched, Patch};
 pub use string_buf::StringBuf;
 
 #[derive(Debug)]
 pub struct TranspileReturn {
     pub parser_panicked: bool,
-    pub parser_errors: std::vec::Vec<oxc_diagnostics::OxcDiagnostic>,
+    pub parser_errors: std::vec::Vec<OxcDiagnostic>,
+    /// Diagnostics reported by the transpiler itself, e.g. non-erasable syntax in [`TranspileOptions::erasable_syntax_only`] mode.
+    pub diagnostics: std::vec::Vec<OxcDiagnostic>,
+    /// Only generated with [`TranspileOptions::source_map`].
+    pub source_map: Option<SourceMap>,
+    /// 0-based, end-exclusive ranges of lines whose column positions differ between the input and the output.
+    /// Positions of all other lines are preserved. Only computed with [`TranspileOptions::shifted_lines`].
+    pub shifted_lines: std::vec::Vec<Range<u32>>,
 }
 
+/// Transpiles `source` in place.
 pub fn transpile<S: StringBuf>(
     allocator: &Allocator,
     source_type: SourceType,
     source: &mut S,
+    options: &TranspileOptions,
+) -> TranspileReturn {
+    let (patches, ret) = transpile_patches(allocator, source_type, source.as_ref(), options);
+    if let Some(patches) = patches {
+        unsafe { apply_patches(&patches, source) };
+    }
+    ret
+}
+
+/// Transpiles a UTF-16 `source` in place.
+///
+/// This is a convenience for callers holding UTF-16 buffers, not a faster path: `source` is still decoded to a UTF-8 copy for parsing,
+/// and the patches are translated back to UTF-16 offsets. Only encoding the output back is saved.
+/// Each blanked UTF-16 code unit (instead of each UTF-8 byte) is replaced with a space, so that UTF-16 columns are preserved.
+///
+/// Spans in `parser_errors` and `diagnostics` are UTF-16 offsets of `source`. `source_map` is never generated.
+pub fn transpile_utf16(
+    allocator: &Allocator,
+    source_type: SourceType,
+    source: &mut std::vec::Vec<u16>,
+    options: &TranspileOptions,
 ) -> TranspileReturn {
+    let (patches, ret) = transpile_utf16_patches(allocator, source_type, source, options);
+    if let Some(patches) = patches {
+        utf16::apply_patches(&patches, source);
+    }
+    ret
+}
+
+/// Same as [`transpile_utf16`], but on a buffer that can't grow, like a `Uint16Array` passed from JavaScript.
+///
+/// If the output has the same length as `source`, which is always the case for sources with only erasable syntax,
+/// it's written to `source` in place, and `None` is returned. Otherwise `source` is left untouched and the output is returned.
+pub fn transpile_utf16_slice(
+    allocator: &Allocator,
+    source_type: SourceType,
+    source: &mut [u16],
+    options: &TranspileOptions,
+) -> (TranspileReturn, Option<std::vec::Vec<u16>>) {
+    let (patches, ret) = transpile_utf16_patches(allocator, source_type, source, options);
+    let Some(patches) = patches else {
+        return (ret, None);
+    };
+    (ret, utf16::apply_patches_to_slice(&patches, source))
+}
+
+/// Same as [`transpile_utf16_slice`], but returns [`TranspileError`] like [`try_transpile`].
+///
+/// `source` is left untouched if an error is returned.
+pub fn try_transpile_utf16_slice(
+    allocator: &Allocator,
+    source_type: SourceType,
+    source: &mut [u16],
+    options: &TranspileOptions,
+) -> Result<(TranspileInPlaceOutput, Option<std::vec::Vec<u16>>), TranspileError> {
+    let utf8_source = utf16::decode(source);
+    let (patches, mut parser_errors) =
+        try_transpile_patches(allocator, source_type, &utf8_source, options)
+            .map_err(|error| utf16::translate_error(&utf8_source, error))?;
+    for error in &mut parser_errors {
+        utf16::translate_error_info(&utf8_source, error);
+    }
+    let patches = utf16::translate_patches(&utf8_source, &patches);
+    let shifted_lines = if options.shifted_lines {
+        utf16::shifted_line_ranges(source, &patches)
+    } else {
+        vec![]
+    };
+    let output = TranspileInPlaceOutput {
+        parser_errors,
+        source_map: None,
+        shifted_lines,
+    };
+    Ok((output, utf16::apply_patches_to_slice(&patches, source)))
+}
+
+/// Decodes and parses a UTF-16 `source`, and collects the patches translated to UTF-16 offsets.
+/// Returns `None` as patches if the parser panicked.
+fn transpile_utf16_patches<'alloc>(
+    allocator: &'alloc Allocator,
+    source_type: SourceType,
+    source: &[u16],
+    options: &TranspileOptions,
+) -> (
+    Option<std::vec::Vec<utf16::Utf16Patch<'alloc>>>,
+    TranspileReturn,
+) {
+    let utf8_source = utf16::decode(source);
+    let ParsedPatches {
+        patches,
+        parser_errors,
+        diagnostics,
+    } = parse_patches(allocator, source_type, &utf8_source, options);
+    let parser_errors = utf16::translate_diagnostics(&utf8_source, parser_errors);
+    let diagnostics = utf16::translate_diagnostics(&utf8_source, diagnostics);
+
+    let Some(patches) = patches else {
+        return (
+            None,
+            TranspileReturn {
+                parser_panicked: true,
+                parser_errors,
+                diagnostics,
+                source_map: None,
+                shifted_lines: vec![],
+            },
+        );
+    };
+    let patches = utf16::translate_patches(&utf8_source, &patches);
+    let shifted_lines = if options.shifted_lines {
+        utf16::shifted_line_ranges(source, &patches)
+    } else {
+        vec![]
+    };
+
+    (
+        Some(patches),
+        TranspileReturn {
+            parser_panicked: false,
+            parser_errors,
+            diagnostics,
+            source_map: None,
+            shifted_lines,
+        },
+    )
+}
+
+/// Transpiles `source` and appends the output to `out`, without copying `source` first.
+///
+/// Nothing is appended if the parser panicked.
+pub fn transpile_to_string(
+    allocator: &Allocator,
+    source_type: SourceType,
+    source: &str,
+    out: &mut std::string::String,
+    options: &TranspileOptions,
+) -> TranspileReturn {
+    let (patches, ret) = transpile_patches(allocator, source_type, source, options);
+    if let Some(patches) = patches {
+        out.reserve(patched_len(source, &patches));
+        // SAFETY: patches are on char boundaries, so only valid utf8 is written. Writing to a Vec never fails.
+        unsafe { write_patched(&patches, source, out.as_mut_vec()) }.unwrap();
+    }
+    ret
+}
+
+/// Transpiles `source` and writes the output to `out`, streaming the unchanged regions and the replacements forward.
+/// `Vec<u8>` is also accepted here.
+///
+/// Nothing is written if the parser panicked.
+pub fn transpile_to_writer<W: io::Write>(
+    allocator: &Allocator,
+    source_type: SourceType,
+    source: &str,
+    out: &mut W,
+    options: &TranspileOptions,
+) -> io::Result<TranspileReturn> {
+    let (patches, ret) = transpile_patches(allocator, source_type, source, options);
+    if let Some(patches) = patches {
+        unsafe { write_patched(&patches, source, out) }?;
+    }
+    Ok(ret)
+}
+
+#[derive(Debug)]
+pub struct TranspileOutput {
+    pub code: std::string::String,
+    /// Syntax errors that the parser recovered from.
+    pub parser_errors: std::vec::Vec<ErrorInfo>,
+    /// Only generated with [`TranspileOptions::source_map`].
+    pub source_map: Option<SourceMap>,
+    /// See [`TranspileReturn::shifted_lines`].
+    pub shifted_lines: std::vec::Vec<Range<u32>>,
+}
+
+/// Transpiles `source` into a new string, or returns the reason why it can't be transpiled.
+///
+/// Unlike [`transpile`], diagnostics reported by the transpiler (e.g. in [`TranspileOptions::erasable_syntax_only`] mode)
+/// are returned as [`TranspileError::UnsupportedSyntax`], and the patches are validated before they are applied.
+pub fn try_transpile(
+    allocator: &Allocator,
+    source_type: SourceType,
+    source: &str,
+    options: &TranspileOptions,
+) -> Result<TranspileOutput, TranspileError> {
+    let (patches, parser_errors) = try_transpile_patches(allocator, source_type, source, options)?;
+
+    let (source_map, shifted_lines) = unsafe { position_info(source, &patches, options) };
+    let mut code = std::string::String::with_capacity(patched_len(source, &patches));
+    // SAFETY: patches are validated. Writing to a Vec never fails.
+    unsafe { write_patched(&patches, source, code.as_mut_vec()) }.unwrap();
+
+    Ok(TranspileOutput {
+        code,
+        parser_errors,
+        source_map,
+        shifted_lines,
+    })
+}
+
+/// Same as [`TranspileOutput`], without the code that is written in place.
+#[derive(Debug)]
+pub struct TranspileInPlaceOutput {
+    /// Syntax errors that the parser recovered from.
+    pub parser_errors: std::vec::Vec<ErrorInfo>,
+    /// Only generated with [`TranspileOptions::source_map`].
+    pub source_map: Option<SourceMap>,
+    /// See [`TranspileReturn::shifted_lines`].
+    pub shifted_lines: std::vec::Vec<Range<u32>>,
+}
+
+/// Same as [`try_transpile`], but transpiles `source` in place like [`transpile`].
+///
+/// `source` is left untouched if an error is returned.
+pub fn try_transpile_in_place<S: StringBuf>(
+    allocator: &Allocator,
+    source_type: SourceType,
+    source: &mut S,
+    options: &TranspileOptions,
+) -> Result<TranspileInPlaceOutput, TranspileError> {
+    let (patches, parser_errors) =
+        try_transpile_patches(allocator, source_type, source.as_ref(), options)?;
+
+    let (source_map, shifted_lines) = unsafe { position_info(source.as_ref(), &patches, options) };
+    // SAFETY: patches are validated.
+    unsafe { apply_patches(&patches, source) };
+
+    Ok(TranspileInPlaceOutput {
+        parser_errors,
+        source_map,
+        shifted_lines,
+    })
+}
+
+/// Parses `source`, and collects and validates the patches.
+/// Returns them with the syntax errors that the parser recovered from.
+fn try_transpile_patches<'alloc>(
+    allocator: &'alloc Allocator,
+    source_type: SourceType,
+    source: &str,
+    options: &TranspileOptions,
+) -> Result<(Vec<'alloc, Patch<'alloc>>, std::vec::Vec<ErrorInfo>), TranspileError> {
+    let ParsedPatches {
+        patches,
+        parser_errors,
+        diagnostics,
+    } = parse_patches(allocator, source_type, source, options);
+
+    let Some(patches) = patches else {
+        return Err(TranspileError::Parse {
+            errors: parser_errors.iter().map(ErrorInfo::from).collect(),
+        });
+    };
+    if !diagnostics.is_empty() {
+        return Err(TranspileError::UnsupportedSyntax {
+            errors: diagnostics.iter().map(ErrorInfo::from).collect(),
+        });
+    }
+    if let Err(invalid_patch) = validate_patches(source, &patches) {
+        return Err(TranspileError::Internal {
+            error: ErrorInfo {
+                message: format!("Invalid patch generated: {:?}", invalid_patch),
+                labels: vec![ErrorLabel {
+                    start: invalid_patch.span.start,
+                    end: invalid_patch.span.end,
+                    message: None,
+                }],
+                help: Some("This is a bug of oxidase. Please report it.".to_owned()),
+            },
+        });
+    }
+    Ok((patches, parser_errors.iter().map(ErrorInfo::from).collect()))
+}
+
+#[derive(Debug)]
+pub struct ComputeEditsReturn<'alloc> {
+    /// Sorted and non-overlapping. Empty if the parser panicked.
+    pub edits: std::vec::Vec<Edit<'alloc>>,
+    pub transpile_return: TranspileReturn,
+}
+
+/// Computes the edits that [`transpile`] would apply to `source`, without applying them.
+///
+/// See [`Edit`] for how each edit applies, and [`lsp`] for converting them to LSP-style text edits.
+pub fn compute_edits<'alloc>(
+    allocator: &'alloc Allocator,
+    source_type: SourceType,
+    source: &str,
+    options: &TranspileOptions,
+) -> ComputeEditsReturn<'alloc> {
+    let (patches, transpile_return) = transpile_patches(allocator, source_type, source, options);
+    let edits = patches
+        .map(|patches| patches.iter().copied().map(Edit::from).collect())
+        .unwrap_or_default();
+    ComputeEditsReturn {
+        edits,
+        transpile_return,
+    }
+}
+
+/// Length of `source` after `patches` are applied.
+fn patched_len(source: &str, patches: &[Patch<'_>]) -> usize {
+    source.len()
+        + patches
+            .iter()
+            .map(|patch| {
+                patch
+                    .replacement
+                    .len()
+                    .saturating_sub(patch.span.size() as usize)
+            })
+            .sum::<usize>()
+}
+
+struct ParsedPatches<'alloc> {
+    /// `None` if the parser panicked.
+    patches: Option<Vec<'alloc, Patch<'alloc>>>,
+    parser_errors: std::vec::Vec<OxcDiagnostic>,
+    diagnostics: std::vec::Vec<OxcDiagnostic>,
+}
+
+/// Parses `source` and collects the patches.
+fn parse_patches<'alloc>(
+    allocator: &'alloc Allocator,
+    source_type: SourceType,
+    source: &str,
+    options: &TranspileOptions,
+) -> ParsedPatches<'alloc> {
     // we are here to transpile, not validate. Be as loose as possible.
     let parser_options = ParseOptions {
         allow_return_outside_function: true,
@@ -37,15 +398,20 @@ pub fn transpile<S: StringBuf>(
         ..Default::default()
     };
 
-    let parser = Parser::new(allocator, source.as_ref(), source_type).with_options(parser_options);
-    let handler = StripHandler::new(allocator, source.as_ref());
+    let parser = Parser::new(allocator, source, source_type).with_options(parser_options);
+    let handler = StripHandler::new(allocator, source, *options);
 
     const VOID_ALLOCATOR: VoidAllocator = VoidAllocator::new();
     let mut parser_ret = parser.parse_with(&VOID_ALLOCATOR, handler);
+    if !parser_ret.panicked {
+        parser_ret.handler.finish();
+    }
+    let diagnostics = parser_ret.handler.take_diagnostics();
     if parser_ret.panicked {
-        return TranspileReturn {
-            parser_panicked: true,
+        return ParsedPatches {
+            patches: None,
             parser_errors: parser_ret.errors,
+            diagnostics,
         };
     }
     let errors = std::mem::take(&mut parser_ret.errors);
@@ -53,12 +419,71 @@ pub fn transpile<S: StringBuf>(
     let handler = parser_ret.handler;
     debug_assert_eq!(handler.scope_len(), 0);
 
-    let patches = handler.into_patches();
-
-    unsafe { apply_patches(&patches, source) };
-
-    TranspileReturn {
-        parser_panicked: false,
+    ParsedPatches {
+        patches: Some(handler.into_patches()),
         parser_errors: errors,
+        diagnostics,
     }
 }
+
+/// Generates the source map and the shifted line ranges if enabled.
+///
+/// # Safety
+///
+/// Same as [`apply_patches`].
+unsafe fn position_info(
+    source: &str,
+    patches: &[Patch<'_>],
+    options: &TranspileOptions,
+) -> (Option<SourceMap>, std::vec::Vec<Range<u32>>) {
+    let source_map = if options.source_map {
+        Some(unsafe { SourceMap::from_patches(source, patches) })
+    } else {
+        None
+    };
+    let shifted_lines = if options.shifted_lines {
+        shifted_line_ranges(source, patches)
+    } else {
+        vec![]
+    };
+    (source_map, shifted_lines)
+}
+
+/// Parses `source` and collects the patches. Returns `None` as patches if the parser panicked.
+fn transpile_patches<'alloc>(
+    allocator: &'alloc Allocator,
+    source_type: SourceType,
+    source: &str,
+    options: &TranspileOptions,
+) -> (Option<Vec<'alloc, Patch<'alloc>>>, TranspileReturn) {
+    let ParsedPatches {
+        patches,
+        parser_errors,
+        diagnostics,
+    } = parse_patches(allocator, source_type, source, options);
+
+    let Some(patches) = patches else {
+        return (
+            None,
+            TranspileReturn {
+                parser_panicked: true,
+                parser_errors,
+                diagnostics,
+                source_map: None,
+                shifted_lines: vec![],
+            },
+        );
+    };
+    let (source_map, shifted_lines) = unsafe { position_info(source, &patches, options) };
+
+    (
+        Some(patches),
+        TranspileReturn {
+            parser_panicked: false,
+            parser_errors,
+            diagnostics,
+            source_map,
+            shifted_lines,
+        },
+    )
+}
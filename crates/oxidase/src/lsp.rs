@@ -0,0 +1,151 @@
+//! Conversions from [`Edit`]s to [LSP](https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/)-style text edits.
+
+use crate::Edit;
+
+/// A zero-based line and UTF-16 column position, like LSP's `Position`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position {
+    pub line: u32,
+    /// In UTF-16 code units.
+    pub character: u32,
+}
+
+/// Like LSP's `Range`. `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// Like LSP's `TextEdit`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+/// Maps byte offsets of a source text to [`Position`]s.
+///
+/// Lines are separated by `\n`, `\r\n` or `\r`, the line terminators recognized by LSP.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    // byte offsets of the start of each line
+    line_starts: Vec<u32>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let bytes = source.as_bytes();
+        let mut line_starts = vec![0u32];
+        let mut pos = 0usize;
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'\r' if bytes.get(pos + 1) == Some(&b'\n') => pos += 2,
+                b'\r' | b'\n' => pos += 1,
+                _ => {
+                    pos += 1;
+                    continue;
+                }
+            }
+            line_starts.push(pos as u32);
+        }
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// Panics if `offset` is out of bounds or not on a char boundary.
+    pub fn position(&self, offset: u32) -> Position {
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let line_start = self.line_starts[line] as usize;
+        let character = self.source[line_start..offset as usize]
+            .encode_utf16()
+            .count();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    pub fn text_edit(&self, edit: &Edit<'_>) -> TextEdit {
+        TextEdit {
+            range: Range {
+                start: self.position(edit.span.start),
+                end: self.position(edit.span.end),
+            },
+            new_text: edit.new_text(self.source),
+        }
+    }
+}
+
+/// Converts `edits` computed on `source` to [`TextEdit`]s.
+pub fn to_text_edits(source: &str, edits: &[Edit<'_>]) -> Vec<TextEdit> {
+    let line_index = LineIndex::new(source);
+    edits
+        .iter()
+        .map(|edit| line_index.text_edit(edit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PatchKind;
+
+    #[test]
+    fn text_edits() {
+        let source = "let a: 'é'\r\nlet 😀: T\nx";
+        let edits = [
+            Edit {
+                span: (5..11).into(),
+                replacement: "",
+                kind: PatchKind::Other,
+            },
+            Edit {
+                span: (21..24).into(),
+                replacement: "",
+                kind: PatchKind::Other,
+            },
+        ];
+        let position = |line, character| Position { line, character };
+        assert_eq!(
+            to_text_edits(source, &edits),
+            vec![
+                TextEdit {
+                    range: Range {
+                        start: position(0, 5),
+                        end: position(0, 10),
+                    },
+                    new_text: "      ".to_owned(),
+                },
+                TextEdit {
+                    range: Range {
+                        start: position(1, 6),
+                        end: position(1, 9),
+                    },
+                    new_text: "   ".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn blanked_line_terminators() {
+        let source = "a\r\nb\nc";
+        let edit = Edit {
+            span: (0..5).into(),
+            replacement: "x",
+            kind: PatchKind::Other,
+        };
+        assert_eq!(edit.new_text(source), "x\r\n \n");
+        assert_eq!(
+            LineIndex::new(source).position(5),
+            Position {
+                line: 2,
+                character: 0
+            }
+        );
+    }
+}
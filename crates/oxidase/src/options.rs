@@ -0,0 +1,244 @@
+use crate::ImportedConstEnums;
+
+/// Options for [`transpile`](crate::transpile).
+///
+/// Non-erasable constructs whose transform is disabled are left untouched in the output.
+#[derive(Debug, Clone, Copy)]
+pub struct TranspileOptions<'a> {
+    /// Transform `enum` declarations into IIFEs. `declare enum` is always stripped.
+    pub transform_enums: bool,
+    /// Erase `const enum` declarations like interfaces instead of transforming them, like tsc without `preserveConstEnums`.
+    /// Accesses of their constant members in the same file are still inlined,
+    /// and other references at runtime are reported in [`TranspileReturn::diagnostics`](crate::TranspileReturn::diagnostics).
+    pub erase_const_enums: bool,
+    /// Transform instantiated namespaces (`namespace A { ... }`/`module A { ... }`) into IIFEs.
+    /// Namespaces containing only types are always stripped.
+    pub transform_namespaces: bool,
+    /// Emit enums and namespaces as pure-annotated IIFEs assigned to their names
+    /// (`var A=/*#__PURE__*/(function(A){...return A})(A||{});`),
+    /// so that bundlers can remove unused ones. Merged declarations share the same object.
+    pub pure_iifes: bool,
+    /// Transform parameter properties (`constructor(public a) {}`) into field declarations and assignments.
+    pub transform_parameter_properties: bool,
+    /// Define class fields with `Object.defineProperty` semantics, like TypeScript's `useDefineForClassFields`.
+    /// When disabled, fields without initializers are erased, parameter properties get no field declarations,
+    /// instance fields with initializers are assigned in constructors after `super()` and parameter properties,
+    /// and static ones in static blocks at the end of the class bodies.
+    /// Their initializers stay in place, wrapped in private methods (`a = 1` -> `#init_0(){return(1)}`, `this.a=this.#init_0();`).
+    ///
+    /// Private (`#a`), computed (`[a]`) and decorated fields are left untouched, so they keep define semantics,
+    /// unlike tsc, which assigns computed and decorated ones too.
+    pub use_define_for_class_fields: bool,
+    /// Transform decorators with the legacy semantics of TypeScript's `experimentalDecorators` into `__decorate`/`__param` calls, like tsc.
+    /// Decorators are applied after the classes in tsc's order: `__decorate([a],A.prototype,"m",null);` for instance members,
+    /// then static members, then `A=__decorate([b],A);` for the class. Class decorator expressions are kept in place,
+    /// while member and parameter decorators are copied. Only named class declarations are supported.
+    /// Decorators on `accessor` fields, and class decorators of classes referencing themselves in their bodies are reported as unsupported.
+    /// The helpers are declared after the last top-level statement, unless they are declared or imported at the top level (e.g. from `tslib`).
+    pub experimental_decorators: bool,
+    /// With [`experimental_decorators`](Self::experimental_decorators), also emit `design:type`, `design:paramtypes` and `design:returntype`
+    /// metadata of decorated members and classes as `__metadata` calls, like tsc's `emitDecoratorMetadata`.
+    /// Types are serialized from the annotation syntax only: type references are emitted as `typeof A==="function"?A:Object`,
+    /// and types that can't be resolved syntactically (e.g. aliases of primitives) as `Object`.
+    pub emit_decorator_metadata: bool,
+    /// The module format that `import a = require('a')` and `export = a` are emitted for.
+    pub module_format: ModuleFormat,
+    /// Leave all non-erasable constructs (enums, instantiated namespaces, parameter properties,
+    /// `import a = require('a')` and `export = a`) untouched and report each of them in
+    /// [`TranspileReturn::diagnostics`](crate::TranspileReturn::diagnostics),
+    /// like TypeScript's `--erasableSyntaxOnly`. Overrides the `transform_*` options.
+    pub erasable_syntax_only: bool,
+    /// Generate [`TranspileReturn::source_map`](crate::TranspileReturn::source_map).
+    pub source_map: bool,
+    /// Compute [`TranspileReturn::shifted_lines`](crate::TranspileReturn::shifted_lines).
+    pub shifted_lines: bool,
+    /// Inline accesses of `const enum` members imported from modules in a [`ConstEnumRegistry`](crate::ConstEnumRegistry).
+    /// Imports whose uses are all inlined are erased. Requires `transform_enums`.
+    pub imported_const_enums: Option<ImportedConstEnums<'a>>,
+}
+
+impl Default for TranspileOptions<'_> {
+    fn default() -> Self {
+        Self {
+            transform_enums: true,
+            erase_const_enums: false,
+            transform_namespaces: true,
+            pure_iifes: false,
+            transform_parameter_properties: true,
+            use_define_for_class_fields: true,
+            experimental_decorators: false,
+            emit_decorator_metadata: false,
+            module_format: ModuleFormat::default(),
+            erasable_syntax_only: false,
+            source_map: false,
+            shifted_lines: false,
+            imported_const_enums: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModuleFormat {
+    /// `import a = require('a')` -> `const a = require('a')`
+    ///
+    /// `export = a` -> `module.exports = a`
+    #[default]
+    CommonJs,
+    /// `import a = require('a')` -> `import a from 'a'`
+    ///
+    /// `export = a` -> `export default a`
+    Esm,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{squashed, transpile};
+    use crate::{try_transpile, Allocator, SourceType};
+
+    #[test]
+    fn disabled_transforms() {
+        let source = "enum A { B }\nnamespace N { export const a = 1 }\nclass C { constructor(public a: number) {} }";
+        let output = transpile(
+            source,
+            &TranspileOptions {
+                transform_enums: false,
+                transform_namespaces: false,
+                transform_parameter_properties: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            squashed(&output),
+            "enum A { B } namespace N { export const a = 1 } class C { constructor(public a ) {} }"
+        );
+
+        let output = transpile(source, &TranspileOptions::default());
+        assert!(!output.contains("enum"));
+        assert!(!output.contains("namespace"));
+        assert!(!output.contains("public"));
+    }
+
+    #[test]
+    fn module_format() {
+        let source = "import a = require('a');\nexport = a;";
+        assert_eq!(
+            squashed(&transpile(source, &TranspileOptions::default())),
+            "const a = require('a'); module.exports = a;"
+        );
+        assert_eq!(
+            squashed(&transpile(
+                source,
+                &TranspileOptions {
+                    module_format: ModuleFormat::Esm,
+                    ..Default::default()
+                }
+            )),
+            "import a from 'a' ; export default a;"
+        );
+    }
+
+    #[test]
+    fn erasable_syntax_only() {
+        let allocator = Allocator::default();
+        let options = TranspileOptions {
+            erasable_syntax_only: true,
+            ..Default::default()
+        };
+        let source = "enum A { B }\nnamespace N.M { export const a = 1 }\nclass C { constructor(public a: number) {} }\nimport b = require('b');\nexport = b;";
+        let error = try_transpile(
+            &allocator,
+            SourceType::ts().with_module(true),
+            source,
+            &options,
+        )
+        .unwrap_err();
+        let messages = error
+            .errors()
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "Enum declarations are not erasable",
+                "Instantiated namespaces are not erasable",
+                "Parameter properties are not erasable",
+                "`import =` declarations are not erasable",
+                "`export =` is not erasable",
+            ]
+        );
+
+        let source = "declare enum A { B }\nnamespace N { export type T = 1 }\nlet a: N.T = 1;";
+        assert_eq!(squashed(&transpile(source, &options)), "let a = 1;");
+    }
+
+    #[test]
+    fn declared_decorator_helpers() {
+        let options = TranspileOptions {
+            experimental_decorators: true,
+            ..Default::default()
+        };
+        let output = transpile(
+            "import { __decorate } from 'tslib';\nconst a = (c: any) => c;\n@a class A {}",
+            &options,
+        );
+        assert!(!output.contains("function __decorate("), "{}", output);
+        assert!(output.contains("A=__decorate("), "{}", output);
+
+        let output = transpile(
+            "function __decorate(...args: any[]) {}\nconst a = (c: any) => c;\n@a class A {}",
+            &options,
+        );
+        assert!(!output.contains(";function __decorate("), "{}", output);
+    }
+
+    #[test]
+    fn decorators_name() {
+        let options = TranspileOptions {
+            experimental_decorators: true,
+            ..Default::default()
+        };
+        // Only whole words are avoided
+        let output = transpile(
+            "const A_decorators_a = 1;\nconst a = (c: any) => c;\n@a class A {}",
+            &options,
+        );
+        assert!(output.contains("var A_decorators=()=>["), "{}", output);
+
+        let output = transpile(
+            "const A_decorators = 1;\nconst a = (c: any) => c;\n@a class A {}",
+            &options,
+        );
+        assert!(output.contains("var A_decorators_1=()=>["), "{}", output);
+    }
+
+    #[test]
+    fn unsupported_decorators() {
+        let allocator = Allocator::default();
+        let options = TranspileOptions {
+            experimental_decorators: true,
+            ..Default::default()
+        };
+        let transpile_error = |source: &str| {
+            try_transpile(
+                &allocator,
+                SourceType::ts().with_module(true),
+                source,
+                &options,
+            )
+            .err()
+        };
+        assert!(transpile_error(
+            "const a = (...args: any[]) => {};\nclass A { @a accessor b = 1 }"
+        )
+        .is_some());
+        // `A` in the body would be the undecorated class
+        assert!(transpile_error("const a = (c: any) => c;\n@a class A { static b = A }").is_some());
+        // Member decorators are applied after the class
+        assert!(transpile_error(
+            "const a = (...args: any[]) => (...args: any[]) => {};\n@a() class A { @a(A) m() {} }"
+        )
+        .is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    io,
     mem::{transmute, MaybeUninit},
     ops::Range,
     slice::from_raw_parts_mut,
@@ -6,12 +7,70 @@ use std::{
 
 use oxc_span::Span;
 
+use crate::line_term::contains_line_terminators;
 use crate::string_buf::StringBuf;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Patch<'a> {
     pub span: Span,
     pub replacement: &'a str,
+    pub kind: PatchKind,
+}
+
+/// Why a patch is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum PatchKind {
+    #[default]
+    Other,
+    /// Type annotations, type parameters/arguments, `this` parameters, `implements` clauses, `!` and `?` marks.
+    TypeAnnotation,
+    /// Interfaces, type aliases, index signatures and erased `const enum`s.
+    TypeDeclaration,
+    /// `import type`, `export type` and type-only specifiers.
+    TypeOnlyImportExport,
+    /// `declare`d declarations, abstract members and overload signatures.
+    Ambient,
+    /// TypeScript-only modifiers: `abstract`, `readonly`, `override`, `private`...
+    Modifier,
+    /// `as` and `satisfies` expressions.
+    AsOrSatisfies,
+    /// Parentheses replacing `<T>` in `<T>expr`.
+    TypeAssertion,
+    /// Parentheses preserving the line of the arrow token in arrow functions with type parameters or return types.
+    ArrowFunctionParens,
+    /// `enum A {` -> `var A;(function(A){`
+    EnumHead,
+    /// Initializers and reverse mappings of enum members.
+    EnumMember,
+    /// `}` -> `})(A||(A={}));`
+    EnumTail,
+    /// `Foo.A` -> `1    ` of `const enum` members.
+    ConstEnumAccess,
+    /// Imports of `const enum`s whose accesses are all inlined.
+    ConstEnumImport,
+    /// The IIFE wrapping an instantiated namespace.
+    NamespaceWrapper,
+    /// Assignments of exported declarations in namespaces.
+    NamespaceExport,
+    /// References to exported `let`/`var`s in namespaces, or to exports of earlier blocks of merged namespaces (`x` -> `N.x`).
+    NamespaceExportReference,
+    /// Field declarations of parameter properties.
+    ParameterPropertyDeclaration,
+    /// `this.a=a;` of parameter properties in constructors.
+    ParameterPropertyInit,
+    /// Class fields without `use_define_for_class_fields`: erased fields without initializers,
+    /// private methods wrapping initializers, and assignments in constructors.
+    ClassField,
+    /// Decorators lowered to `__decorate`/`__param`/`__metadata` calls with `experimental_decorators`, and the helpers.
+    Decorator,
+    /// `import a = ...`
+    ImportEquals,
+    /// `export = a`
+    ExportAssignment,
+    /// A `;` added to avoid ASI hazards.
+    /// It's added to an existing patch, whose kind is overwritten.
+    Asi,
 }
 
 impl<'a> From<Span> for Patch<'a> {
@@ -19,6 +78,7 @@ impl<'a> From<Span> for Patch<'a> {
         Patch {
             span,
             replacement: "",
+            kind: PatchKind::Other,
         }
     }
 }
@@ -28,6 +88,7 @@ impl<'a> From<Range<u32>> for Patch<'a> {
         Patch {
             span: range.into(),
             replacement: "",
+            kind: PatchKind::Other,
         }
     }
 }
@@ -37,6 +98,7 @@ impl<'a, S: Into<Span>> From<(S, &'a str)> for Patch<'a> {
         Patch {
             span: span.into(),
             replacement,
+            kind: PatchKind::Other,
         }
     }
 }
@@ -182,7 +244,6 @@ pub unsafe fn apply_patches(patches: &[Patch<'_>], source: &mut impl StringBuf)
 
         #[cfg(debug_assertions)]
         {
-            use crate::line_term::contains_line_terminators;
             use std::cmp::min;
             use std::str::from_utf8;
 
@@ -219,6 +280,95 @@ pub unsafe fn apply_patches(patches: &[Patch<'_>], source: &mut impl StringBuf)
     unsafe { source.set_len(src_len + additional) };
 }
 
+/// Checks the safety requirements of [`apply_patches`], and that no line terminator is added or removed.
+/// Returns the first invalid patch.
+pub(crate) fn validate_patches<'a>(source: &str, patches: &[Patch<'a>]) -> Result<(), Patch<'a>> {
+    let mut last_patch_end: u32 = 0;
+    for patch in patches {
+        let Patch {
+            span, replacement, ..
+        } = *patch;
+        let is_valid = last_patch_end <= span.start
+            && span.start <= span.end
+            && source.is_char_boundary(span.start as usize)
+            && source.is_char_boundary(span.end as usize)
+            && !contains_line_terminators(replacement.as_bytes())
+            && !contains_line_terminators(
+                &source.as_bytes()[span.start as usize
+                    ..(span.start as usize + replacement.len()).min(span.end as usize)],
+            );
+        if !is_valid {
+            return Err(*patch);
+        }
+        last_patch_end = span.end;
+    }
+    Ok(())
+}
+
+/// Writes `source` with `patches` applied to `out`, streaming the unchanged regions and the replacements forward.
+/// The output is identical to what [`apply_patches`] produces in place.
+///
+/// # Safety
+///
+/// Same as [`apply_patches`].
+pub unsafe fn write_patched(
+    patches: &[Patch<'_>],
+    source: &str,
+    out: &mut impl io::Write,
+) -> io::Result<()> {
+    let source = source.as_bytes();
+    let mut last_patch_end: usize = 0;
+    for patch in patches {
+        let patch_start = patch.span.start as usize;
+        let patch_end = patch.span.end as usize;
+        debug_assert!(
+            last_patch_end <= patch_start,
+            "Unordered/overlapped patches: {:?}",
+            patches
+        );
+
+        // write substring before patch span
+        out.write_all(&source[last_patch_end..patch_start])?;
+        // write replacement
+        out.write_all(patch.replacement.as_bytes())?;
+        // write whitespaces after replacement
+        let blank_start = (patch_start + patch.replacement.len()).min(patch_end);
+        write_whitespaces_preserving_newlines(&source[blank_start..patch_end], out)?;
+
+        last_patch_end = patch_end;
+    }
+    out.write_all(&source[last_patch_end..])
+}
+
+pub(crate) fn write_whitespaces_preserving_newlines(
+    src: &[u8],
+    out: &mut impl io::Write,
+) -> io::Result<()> {
+    const SPACES: [u8; 64] = [b' '; 64];
+    let mut pos = 0;
+    while pos < src.len() {
+        let line_term_len = match &src[pos..] {
+            [b'\r' | b'\n', ..] => 1,
+            [226, 128, 168 | 169, ..] => 3,
+            _ => 0,
+        };
+        if line_term_len > 0 {
+            out.write_all(&src[pos..pos + line_term_len])?;
+            pos += line_term_len;
+            continue;
+        }
+        let space_count = src[pos..]
+            .iter()
+            .take(SPACES.len())
+            .take_while(|byte| !matches!(byte, b'\r' | b'\n' | 226))
+            .count()
+            .max(1);
+        out.write_all(&SPACES[..space_count])?;
+        pos += space_count;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,10 +379,12 @@ mod tests {
             Patch {
                 span: (0..0).into(),
                 replacement: "x",
+                ..Default::default()
             },
             Patch {
                 span: (1..3).into(),
                 replacement: "0",
+                ..Default::default()
             },
         ];
         unsafe { apply_patches(&patches, &mut source) };
@@ -245,8 +397,72 @@ mod tests {
         let patches = [Patch {
             span: (0..source.len() as u32).into(),
             replacement: "",
+            ..Default::default()
         }];
         unsafe { apply_patches(&patches, &mut source) };
         assert_eq!(source.as_str(), "   \n ");
     }
+
+    #[test]
+    fn validate() {
+        let source = "ab\nc\u{2028}";
+        let patch = |range: Range<u32>, replacement| Patch {
+            span: range.into(),
+            replacement,
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_patches(source, &[patch(0..4, "x"), patch(4..7, "")]),
+            Ok(())
+        );
+        // overlapped
+        assert_eq!(
+            validate_patches(source, &[patch(0..2, ""), patch(1..2, "")]),
+            Err(patch(1..2, ""))
+        );
+        // not char boundary
+        assert_eq!(
+            validate_patches(source, &[patch(4..5, "")]),
+            Err(patch(4..5, ""))
+        );
+        // replacing line terminators
+        assert_eq!(
+            validate_patches(source, &[patch(0..4, "abc")]),
+            Err(patch(0..4, "abc"))
+        );
+        // inserting line terminators
+        assert_eq!(
+            validate_patches(source, &[patch(0..0, "\n")]),
+            Err(patch(0..0, "\n"))
+        );
+    }
+
+    #[test]
+    fn write_forward() {
+        let source = "abc\r\nd\u{2028}ef";
+        let patches = [
+            Patch {
+                span: (0..0).into(),
+                replacement: "x",
+                ..Default::default()
+            },
+            Patch {
+                span: (1..9).into(),
+                replacement: "0",
+                ..Default::default()
+            },
+            Patch {
+                span: (9..10).into(),
+                replacement: "123",
+                ..Default::default()
+            },
+        ];
+        let mut out = Vec::<u8>::new();
+        unsafe { write_patched(&patches, source, &mut out) }.unwrap();
+
+        let mut in_place = source.to_owned();
+        unsafe { apply_patches(&patches, &mut in_place) };
+        assert_eq!(String::from_utf8(out).unwrap(), in_place);
+        assert_eq!(in_place, "xa0 \r\n \u{2028}123f");
+    }
 }
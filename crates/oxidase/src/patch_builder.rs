@@ -1,8 +1,10 @@
 use std::ops::{Deref, DerefMut};
 
+use bumpalo::format;
 use oxc_allocator::{Allocator, Vec};
+use oxc_span::Span;
 
-use crate::Patch;
+use crate::patch::{Patch, PatchKind};
 
 pub struct PatchBuilder<'alloc> {
     // source: &'source [u8],
@@ -32,8 +34,11 @@ impl<'alloc> PatchBuilder<'alloc> {
         self.patches.len()
     }
 
-    pub fn push(&mut self, patch: impl Into<Patch<'alloc>>) {
-        let patch = patch.into();
+    pub fn push(&mut self, kind: PatchKind, patch: impl Into<Patch<'alloc>>) {
+        let patch = Patch {
+            kind,
+            ..patch.into()
+        };
         if cfg!(debug_assertions) {
             if let Some(last_patch) = self.patches.last() {
                 assert!(patch.span.start >= last_patch.span.end);
@@ -42,8 +47,11 @@ impl<'alloc> PatchBuilder<'alloc> {
         self.patches.push(patch);
     }
 
-    pub fn push_merging_tail(&mut self, patch: impl Into<Patch<'alloc>>) {
-        let patch = patch.into();
+    pub fn push_merging_tail(&mut self, kind: PatchKind, patch: impl Into<Patch<'alloc>>) {
+        let patch = Patch {
+            kind,
+            ..patch.into()
+        };
         debug_assert!(
             patch.span.end >= self.patches.last().map(|patch| patch.span.end).unwrap_or(0)
         );
@@ -55,8 +63,65 @@ impl<'alloc> PatchBuilder<'alloc> {
         self.patches.push(patch);
     }
 
-    pub fn binary_search_insert(&mut self, patch: impl Into<Patch<'alloc>>) {
-        let patch = patch.into();
+    /// Appends statements in `code` to the replacement of the patch at `index`, separated by `;` if needed.
+    pub fn append_statements(&mut self, index: usize, code: &str, allocator: &'alloc Allocator) {
+        let patch = &mut self.patches[index];
+        let separator = if patch.replacement.ends_with(';') {
+            ""
+        } else {
+            ";"
+        };
+        patch.replacement =
+            format!(in allocator, "{}{}{}", patch.replacement, separator, code).into_bump_str();
+    }
+
+    /// Inserts `replacement` at `pos`, after existing insertions at `pos` (e.g. the tail of a namespace ending at `pos`).
+    pub fn insert_at(&mut self, pos: u32, kind: PatchKind, replacement: &'alloc str) {
+        let index = self
+            .patches
+            .partition_point(|p| p.span.start < pos || p.span == Span::new(pos, pos));
+        debug_assert!(index == 0 || self.patches[index - 1].span.end <= pos);
+        self.patches.insert(
+            index,
+            Patch {
+                span: Span::new(pos, pos),
+                replacement,
+                kind,
+            },
+        );
+    }
+
+    /// Inserts `patch`, replacing existing patches inside its span.
+    pub fn binary_search_insert_merging(
+        &mut self,
+        kind: PatchKind,
+        patch: impl Into<Patch<'alloc>>,
+    ) {
+        let patch = Patch {
+            kind,
+            ..patch.into()
+        };
+        let start_pos = self
+            .patches
+            .partition_point(|p| p.span.end <= patch.span.start);
+        let end_pos = self
+            .patches
+            .partition_point(|p| p.span.start < patch.span.end);
+
+        if cfg!(debug_assertions) {
+            for p in &self.patches[start_pos..end_pos] {
+                assert!(p.span.start >= patch.span.start && p.span.end <= patch.span.end);
+            }
+        }
+        self.patches.drain(start_pos..end_pos);
+        self.patches.insert(start_pos, patch);
+    }
+
+    pub fn binary_search_insert(&mut self, kind: PatchKind, patch: impl Into<Patch<'alloc>>) {
+        let patch = Patch {
+            kind,
+            ..patch.into()
+        };
         let insert_pos = self
             .patches
             .partition_point(|p| p.span.end <= patch.span.start);
@@ -87,8 +152,11 @@ impl<'alloc> PatchBuilder<'alloc> {
     pub fn truncate(&mut self, len: usize) {
         self.patches.truncate(len);
     }
-    pub fn insert(&mut self, index: usize, patch: impl Into<Patch<'alloc>>) {
-        let patch = patch.into();
+    pub fn insert(&mut self, index: usize, kind: PatchKind, patch: impl Into<Patch<'alloc>>) {
+        let patch = Patch {
+            kind,
+            ..patch.into()
+        };
         if cfg!(debug_assertions) {
             if let Some(index_before) = index.checked_sub(1) {
                 assert!(self.patches[index_before].span.end <= patch.span.start);
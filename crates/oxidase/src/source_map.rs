@@ -0,0 +1,356 @@
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use crate::patch::Patch;
+
+/// A [V3 source map](https://tc39.es/ecma426/) from the transpiled output back to the input.
+///
+/// Since line positions are always preserved, each line either maps identically with a single segment,
+/// or, if patches on it changed its length, with a segment at each patch boundary.
+/// Columns are in UTF-16 code units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    mappings: String,
+}
+
+impl SourceMap {
+    pub fn mappings(&self) -> &str {
+        &self.mappings
+    }
+
+    /// Serializes the source map as JSON with `source_name` as the only entry of `sources`.
+    pub fn to_json_string(&self, source_name: &str) -> String {
+        let mut json = String::with_capacity(self.mappings.len() + source_name.len() + 64);
+        json.push_str(r#"{"version":3,"sources":["#);
+        write_json_string(source_name, &mut json);
+        json.push_str(r#"],"names":[],"mappings":""#);
+        json.push_str(&self.mappings);
+        json.push_str(r#""}"#);
+        json
+    }
+
+    /// # Safety
+    ///
+    /// Same as [`apply_patches`](crate::apply_patches): `patches` are sorted, not overlapped, and on char boundaries of `source`.
+    pub(crate) unsafe fn from_patches(source: &str, patches: &[Patch<'_>]) -> Self {
+        let mut builder = MappingsBuilder::default();
+        let source = source.as_bytes();
+        let mut pos = 0usize;
+        for patch in patches {
+            let start = patch.span.start as usize;
+            let end = patch.span.end as usize;
+            builder.verbatim(&source[pos..start]);
+            builder.patched(&source[start..end], patch.replacement);
+            pos = end;
+        }
+        builder.verbatim(&source[pos..]);
+        builder.finish()
+    }
+}
+
+/// 0-based, end-exclusive ranges of lines where some input text ends up in a different column in the output.
+///
+/// Only patches whose replacements are longer than their spans shift columns, because shorter replacements are padded with whitespace.
+pub(crate) fn shifted_line_ranges(source: &str, patches: &[Patch<'_>]) -> Vec<Range<u32>> {
+    let source = source.as_bytes();
+    let mut ranges: Vec<Range<u32>> = Vec::new();
+    let mut line = 0u32;
+    let mut pos = 0usize;
+    for patch in patches {
+        if patch.replacement.len() <= patch.span.size() as usize {
+            continue;
+        }
+        let end = patch.span.end as usize;
+        while pos < end {
+            let line_term_len = line_terminator_len(&source[pos..]);
+            if line_term_len > 0 {
+                line += 1;
+                pos += line_term_len;
+            } else {
+                pos += 1;
+            }
+        }
+        // Nothing to shift if the patch is at the end of the line
+        let rest = &source[end..];
+        let has_text_after = rest[..line_len(rest)]
+            .iter()
+            .any(|byte| !matches!(byte, b' ' | b'\t'));
+        if !has_text_after {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last_range) if last_range.end >= line => last_range.end = line + 1,
+            _ => ranges.push(line..line + 1),
+        }
+    }
+    ranges
+}
+
+/// Length of the line terminator starting at `buf[0]`, or 0 if `buf` doesn't start with one.
+/// `\r\n` is a single line terminator.
+fn line_terminator_len(buf: &[u8]) -> usize {
+    match buf {
+        [b'\r', b'\n', ..] => 2,
+        [b'\n' | b'\r', ..] => 1,
+        [226, 128, 168 | 169, ..] => 3,
+        _ => 0,
+    }
+}
+
+/// Length of `buf` until the first line terminator.
+fn line_len(buf: &[u8]) -> usize {
+    (0..buf.len())
+        .find(|index| line_terminator_len(&buf[*index..]) > 0)
+        .unwrap_or(buf.len())
+}
+
+fn utf16_len_of_byte(byte: u8) -> u32 {
+    match byte {
+        // continuation bytes
+        0b1000_0000..=0b1011_1111 => 0,
+        // leading bytes of 4-byte sequences, which are surrogate pairs in UTF-16
+        0b1111_0000..=0xFF => 2,
+        _ => 1,
+    }
+}
+
+#[derive(Default)]
+struct MappingsBuilder {
+    mappings: String,
+    orig_col: u32,
+    gen_col: u32,
+    line: u32,
+    // (generated column, original column) of the segments in the current line
+    line_segments: Vec<(u32, u32)>,
+    // true if some input text in the current line ends up in a different column
+    line_shifted: bool,
+
+    prev_orig_line: u32,
+    prev_orig_col: u32,
+}
+
+impl MappingsBuilder {
+    fn verbatim(&mut self, mut text: &[u8]) {
+        let mut at_text_start = true;
+        while let Some(&byte) = text.first() {
+            let line_term_len = line_terminator_len(text);
+            if line_term_len > 0 {
+                self.finish_line();
+                text = &text[line_term_len..];
+                at_text_start = true;
+                continue;
+            }
+            if at_text_start && self.gen_col != self.orig_col {
+                self.line_shifted = true;
+            }
+            at_text_start = false;
+            let utf16_len = utf16_len_of_byte(byte);
+            self.orig_col += utf16_len;
+            self.gen_col += utf16_len;
+            text = &text[1..];
+        }
+    }
+
+    fn patched(&mut self, original: &[u8], replacement: &str) {
+        self.line_segments.push((self.gen_col, self.orig_col));
+        // The replacement is written at the start of the span, and the rest of the span is blanked with
+        // a whitespace per byte except line terminators (see `apply_patches`).
+        let mut replacement_len = replacement.len();
+        let mut replacement_utf16_len = replacement.encode_utf16().count() as u32;
+        let mut rest = original;
+        loop {
+            let line_len = line_len(rest);
+            let line = &rest[..line_len];
+            self.orig_col += line.iter().copied().map(utf16_len_of_byte).sum::<u32>();
+            self.gen_col +=
+                replacement_utf16_len + line.len().saturating_sub(replacement_len) as u32;
+            replacement_len = 0;
+            replacement_utf16_len = 0;
+            if line_len == rest.len() {
+                break;
+            }
+            self.finish_line();
+            rest = &rest[line_len + line_terminator_len(&rest[line_len..])..];
+        }
+        self.line_segments.push((self.gen_col, self.orig_col));
+    }
+
+    fn finish_line(&mut self) {
+        let line_segments = std::mem::take(&mut self.line_segments);
+        let mut prev_gen_col = 0u32;
+        let mut is_first = true;
+        let mut write_segment = |builder: &mut Self, gen_col: u32, orig_col: u32| {
+            if !is_first {
+                builder.mappings.push(',');
+            }
+            is_first = false;
+            write_vlq(gen_col as i64 - prev_gen_col as i64, &mut builder.mappings);
+            // source index
+            write_vlq(0, &mut builder.mappings);
+            write_vlq(
+                builder.line as i64 - builder.prev_orig_line as i64,
+                &mut builder.mappings,
+            );
+            write_vlq(
+                orig_col as i64 - builder.prev_orig_col as i64,
+                &mut builder.mappings,
+            );
+            prev_gen_col = gen_col;
+            builder.prev_orig_line = builder.line;
+            builder.prev_orig_col = orig_col;
+        };
+        if self.line_shifted {
+            write_segment(self, 0, 0);
+            for (index, (gen_col, orig_col)) in line_segments.iter().copied().enumerate() {
+                // segments at the same generated column: only the last one matters.
+                let is_overridden = matches!(line_segments.get(index + 1), Some((next_gen_col, _)) if *next_gen_col == gen_col);
+                if gen_col == 0 || is_overridden {
+                    continue;
+                }
+                write_segment(self, gen_col, orig_col);
+            }
+        } else {
+            write_segment(self, 0, 0);
+        }
+        self.mappings.push(';');
+
+        self.line_segments = line_segments;
+        self.line_segments.clear();
+        self.line += 1;
+        self.orig_col = 0;
+        self.gen_col = 0;
+        self.line_shifted = false;
+    }
+
+    fn finish(mut self) -> SourceMap {
+        self.finish_line();
+        // `finish_line` always ends the line with a `;`, which isn't needed after the last line.
+        self.mappings.pop();
+        SourceMap {
+            mappings: self.mappings,
+        }
+    }
+}
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn write_vlq(value: i64, out: &mut String) {
+    let mut vlq = if value < 0 {
+        ((-value as u64) << 1) | 1
+    } else {
+        (value as u64) << 1
+    };
+    loop {
+        let mut digit = (vlq & 0b11111) as u8;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64_CHARS[digit as usize] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+fn write_json_string(value: &str, out: &mut String) {
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", ch as u32).unwrap();
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_map(source: &str, patches: &[Patch<'_>]) -> SourceMap {
+        unsafe { SourceMap::from_patches(source, patches) }
+    }
+
+    #[test]
+    fn vlq() {
+        let mut out = String::new();
+        for value in [0, 1, -1, 15, 16, -16, 1000] {
+            write_vlq(value, &mut out);
+            out.push(',');
+        }
+        assert_eq!(out, "A,C,D,e,gB,hB,w+B,");
+    }
+
+    #[test]
+    fn identical_lines() {
+        let source = "let a: string\nlet b\r\nlet c";
+        let patches = [Patch {
+            span: (5..13).into(),
+            replacement: "",
+            ..Default::default()
+        }];
+        assert_eq!(source_map(source, &patches).mappings(), "AAAA;AACA;AACA");
+    }
+
+    #[test]
+    fn shifted_line() {
+        let source = "a\nA, B\nb";
+        let patches = [
+            Patch {
+                span: (3..4).into(),
+                replacement: ";var A;",
+                ..Default::default()
+            },
+            Patch {
+                span: (6..6).into(),
+                replacement: ";var B;",
+                ..Default::default()
+            },
+        ];
+        // line 1: `A;var A; B;var B;`, `B` moves from column 3 to column 9
+        assert_eq!(
+            source_map(source, &patches).mappings(),
+            "AAAA;AACA,CAAC,OAAC,EAAE,OAAA;AACJ"
+        );
+    }
+
+    #[test]
+    fn shifted_lines() {
+        let source = "enum A { X, Y }\nlet a: string\nenum B {\n  X,\n}";
+        let patches = [
+            Patch {
+                span: (10..11).into(),
+                replacement: ";var X;",
+                ..Default::default()
+            },
+            Patch {
+                span: (21..29).into(),
+                replacement: "",
+                ..Default::default()
+            },
+            Patch {
+                span: (42..43).into(),
+                replacement: ";var X;",
+                ..Default::default()
+            },
+        ];
+        assert_eq!(shifted_line_ranges(source, &patches), vec![0..1]);
+    }
+
+    #[test]
+    fn json() {
+        let source_map = source_map("a", &[]);
+        assert_eq!(
+            source_map.to_json_string("a\"b.ts"),
+            r#"{"version":3,"sources":["a\"b.ts"],"names":[],"mappings":"AAAA"}"#
+        );
+    }
+}
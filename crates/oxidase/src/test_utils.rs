@@ -0,0 +1,25 @@
+use crate::{try_transpile, Allocator, SourceType, TranspileOptions};
+
+/// Transpiles the module `source` with `options`, and checks that the line count is preserved.
+pub fn transpile(source: &str, options: &TranspileOptions) -> String {
+    let allocator = Allocator::default();
+    let output = try_transpile(
+        &allocator,
+        SourceType::ts().with_module(true),
+        source,
+        options,
+    )
+    .unwrap();
+    assert_eq!(
+        output.code.lines().count(),
+        source.lines().count(),
+        "line count changed: {}",
+        output.code
+    );
+    output.code
+}
+
+/// Collapses whitespace, which is inserted in place of erased code.
+pub fn squashed(code: &str) -> String {
+    code.split_whitespace().collect::<Vec<_>>().join(" ")
+}
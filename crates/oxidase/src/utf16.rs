@@ -0,0 +1,337 @@
+use std::ops::Range;
+
+use oxc_diagnostics::{LabeledSpan, OxcDiagnostic};
+
+use crate::error::{ErrorInfo, TranspileError};
+use crate::patch::Patch;
+
+/// A [`Patch`] with its span translated to UTF-16 offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Utf16Patch<'a> {
+    start: u32,
+    /// End of the source chars that `replacement` is written over in UTF-8 (see `apply_patches`),
+    /// which never contain line terminators.
+    prefix_end: u32,
+    end: u32,
+    replacement: &'a str,
+    replacement_len: u32,
+}
+
+impl<'a> Utf16Patch<'a> {
+    fn output_len(&self) -> u32 {
+        self.replacement_len.max(self.prefix_end - self.start) + (self.end - self.prefix_end)
+    }
+}
+
+/// Decodes `source` for parsing. Lone surrogates are replaced with U+FFFD,
+/// which is also a single UTF-16 code unit, so offsets can still be translated.
+pub(crate) fn decode(source: &[u16]) -> String {
+    char::decode_utf16(source.iter().copied())
+        .map(|ch| ch.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Translates `patches` on `utf8_source` to UTF-16 offsets of the same source.
+pub(crate) fn translate_patches<'a>(
+    utf8_source: &str,
+    patches: &[Patch<'a>],
+) -> Vec<Utf16Patch<'a>> {
+    let mut utf8_pos = 0usize;
+    let mut utf16_pos = 0u32;
+    // Moves forward to the first char boundary at or after `utf8_offset`, and returns its UTF-16 offset.
+    let mut translate = |utf8_offset: u32| {
+        for ch in utf8_source[utf8_pos..].chars() {
+            if utf8_pos >= utf8_offset as usize {
+                break;
+            }
+            utf8_pos += ch.len_utf8();
+            utf16_pos += ch.len_utf16() as u32;
+        }
+        utf16_pos
+    };
+    patches
+        .iter()
+        .map(|patch| {
+            let start = translate(patch.span.start);
+            let prefix_end =
+                translate((patch.span.start + patch.replacement.len() as u32).min(patch.span.end));
+            let end = translate(patch.span.end);
+            Utf16Patch {
+                start,
+                prefix_end,
+                end,
+                replacement: patch.replacement,
+                replacement_len: patch.replacement.encode_utf16().count() as u32,
+            }
+        })
+        .collect()
+}
+
+/// Translates a UTF-8 offset of `utf8_source` to the UTF-16 offset of the same position.
+fn translate_offset(utf8_source: &str, utf8_offset: usize) -> usize {
+    utf8_source
+        .char_indices()
+        .take_while(|(index, _)| *index < utf8_offset)
+        .map(|(_, ch)| ch.len_utf16())
+        .sum()
+}
+
+/// Translates label spans of `diagnostics` on `utf8_source` to UTF-16 offsets of the same source.
+pub(crate) fn translate_diagnostics(
+    utf8_source: &str,
+    diagnostics: Vec<OxcDiagnostic>,
+) -> Vec<OxcDiagnostic> {
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| {
+            let Some(labels) = diagnostic.labels.clone() else {
+                return diagnostic;
+            };
+            diagnostic.with_labels(labels.into_iter().map(|label| {
+                let start = translate_offset(utf8_source, label.offset());
+                let end = translate_offset(utf8_source, label.offset() + label.len());
+                LabeledSpan::new(label.label().map(str::to_owned), start, end - start)
+            }))
+        })
+        .collect()
+}
+
+/// Translates label spans of `error` on `utf8_source` to UTF-16 offsets of the same source.
+pub(crate) fn translate_error_info(utf8_source: &str, error: &mut ErrorInfo) {
+    for label in &mut error.labels {
+        label.start = translate_offset(utf8_source, label.start as usize) as u32;
+        label.end = translate_offset(utf8_source, label.end as usize) as u32;
+    }
+}
+
+/// Same as [`translate_error_info`], for all errors of `error`.
+pub(crate) fn translate_error(utf8_source: &str, mut error: TranspileError) -> TranspileError {
+    let errors = match &mut error {
+        TranspileError::Parse { errors } | TranspileError::UnsupportedSyntax { errors } => {
+            errors.as_mut_slice()
+        }
+        TranspileError::Internal { error } => std::slice::from_mut(error),
+    };
+    for error in errors {
+        translate_error_info(utf8_source, error);
+    }
+    error
+}
+
+fn is_line_terminator(unit: u16) -> bool {
+    matches!(unit, 0x0A | 0x0D | 0x2028 | 0x2029)
+}
+
+/// Number of code units that `patches` add to the source.
+pub(crate) fn additional_len(patches: &[Utf16Patch<'_>]) -> usize {
+    patches
+        .iter()
+        .map(|patch| (patch.output_len() - (patch.end - patch.start)) as usize)
+        .sum()
+}
+
+/// Same as [`apply_patches`](crate::apply_patches), but blanks each UTF-16 code unit instead of each UTF-8 byte,
+/// so that UTF-16 columns are preserved.
+pub(crate) fn apply_patches(patches: &[Utf16Patch<'_>], source: &mut Vec<u16>) {
+    let src_len = source.len();
+    source.resize(src_len + additional_len(patches), 0);
+    write_patches(patches, source, src_len);
+}
+
+/// Applies `patches` to `source` in place if the output has the same length, and returns `None`.
+/// Otherwise `source` is left untouched and the output is returned.
+pub(crate) fn apply_patches_to_slice(
+    patches: &[Utf16Patch<'_>],
+    source: &mut [u16],
+) -> Option<Vec<u16>> {
+    if additional_len(patches) == 0 {
+        let src_len = source.len();
+        write_patches(patches, source, src_len);
+        return None;
+    }
+    let mut output = source.to_vec();
+    apply_patches(patches, &mut output);
+    Some(output)
+}
+
+/// Writes the output of `patches` to `buf`, which starts with the source of length `src_len`,
+/// and has room for the output after it.
+pub(crate) fn write_patches(patches: &[Utf16Patch<'_>], buf: &mut [u16], src_len: usize) {
+    // Write backward, so that the source at a position is always read before it's overwritten.
+    let mut pos = buf.len();
+    let mut last_patch_start = src_len;
+    for patch in patches.iter().rev() {
+        let start = patch.start as usize;
+        let prefix_end = patch.prefix_end as usize;
+        let end = patch.end as usize;
+
+        // write substring after patch span
+        pos -= last_patch_start - end;
+        buf.copy_within(end..last_patch_start, pos);
+
+        // write whitespaces after replacement
+        for index in (prefix_end..end).rev() {
+            pos -= 1;
+            let unit = buf[index];
+            buf[pos] = if is_line_terminator(unit) {
+                unit
+            } else {
+                b' ' as u16
+            };
+        }
+        let padding = (prefix_end - start).saturating_sub(patch.replacement_len as usize);
+        pos -= padding;
+        buf[pos..pos + padding].fill(b' ' as u16);
+
+        // write replacement
+        pos -= patch.replacement_len as usize;
+        for (unit, dest) in patch.replacement.encode_utf16().zip(&mut buf[pos..]) {
+            *dest = unit;
+        }
+
+        last_patch_start = start;
+    }
+    debug_assert_eq!(pos, last_patch_start);
+}
+
+/// Same as [`shifted_line_ranges`](crate::source_map::shifted_line_ranges), on the UTF-16 source.
+pub(crate) fn shifted_line_ranges(source: &[u16], patches: &[Utf16Patch<'_>]) -> Vec<Range<u32>> {
+    let mut ranges: Vec<Range<u32>> = Vec::new();
+    let mut line = 0u32;
+    let mut pos = 0usize;
+    for patch in patches {
+        if patch.output_len() <= patch.end - patch.start {
+            continue;
+        }
+        let end = patch.end as usize;
+        while pos < end {
+            if source[pos] == 0x0D && source.get(pos + 1) == Some(&0x0A) {
+                pos += 1;
+            }
+            if is_line_terminator(source[pos]) {
+                line += 1;
+            }
+            pos += 1;
+        }
+        // Nothing to shift if the patch is at the end of the line
+        let has_text_after = source[end..]
+            .iter()
+            .take_while(|unit| !is_line_terminator(**unit))
+            .any(|unit| !matches!(unit, 0x20 | 0x09));
+        if !has_text_after {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last_range) if last_range.end >= line => last_range.end = line + 1,
+            _ => ranges.push(line..line + 1),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transpile_utf16(source: &str, patches: &[Patch<'_>]) -> (String, Vec<Range<u32>>) {
+        let mut utf16_source: Vec<u16> = source.encode_utf16().collect();
+        let utf16_patches = translate_patches(source, patches);
+        let shifted_lines = shifted_line_ranges(&utf16_source, &utf16_patches);
+        apply_patches(&utf16_patches, &mut utf16_source);
+        (String::from_utf16(&utf16_source).unwrap(), shifted_lines)
+    }
+
+    #[test]
+    fn blank_code_units() {
+        // `é` is 2 bytes in UTF-8 but 1 code unit in UTF-16, `😀` is 4 bytes and 2 code units.
+        let source = "let a: 'é'\r\nlet b: '😀' = 1\u{2028}x";
+        let patches = [
+            Patch {
+                span: (5..11).into(),
+                replacement: "",
+                ..Default::default()
+            },
+            Patch {
+                span: (18..26).into(),
+                replacement: "",
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            transpile_utf16(source, &patches),
+            ("let a     \r\nlet b       = 1\u{2028}x".to_owned(), vec![])
+        );
+    }
+
+    #[test]
+    fn replacements() {
+        let source = "A, é\nB";
+        let patches = [
+            Patch {
+                span: (1..2).into(),
+                replacement: ";var A;",
+                ..Default::default()
+            },
+            Patch {
+                span: (3..6).into(),
+                replacement: "x",
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            transpile_utf16(source, &patches),
+            ("A;var A; x\nB".to_owned(), vec![0..1])
+        );
+    }
+
+    #[test]
+    fn lone_surrogate() {
+        let mut source: Vec<u16> = "a: T".encode_utf16().collect();
+        source.insert(0, 0xD800);
+        let utf8_source = decode(&source);
+        let patches = translate_patches(
+            &utf8_source,
+            &[Patch {
+                span: (4..7).into(),
+                replacement: "",
+                ..Default::default()
+            }],
+        );
+        apply_patches(&patches, &mut source);
+        let mut expected: Vec<u16> = "a   ".encode_utf16().collect();
+        expected.insert(0, 0xD800);
+        assert_eq!(source, expected);
+    }
+
+    #[test]
+    fn error_spans() {
+        let diagnostics = translate_diagnostics(
+            "'😀é'; x",
+            vec![OxcDiagnostic::error("x").with_label(oxc_span::Span::new(10, 11))],
+        );
+        let label = &diagnostics[0].labels.as_ref().unwrap()[0];
+        assert_eq!((label.offset(), label.len()), (7, 1));
+
+        let error = translate_error(
+            "é: x",
+            TranspileError::UnsupportedSyntax {
+                errors: vec![ErrorInfo {
+                    message: "x".to_owned(),
+                    labels: vec![crate::ErrorLabel {
+                        start: 2,
+                        end: 5,
+                        message: None,
+                    }],
+                    help: None,
+                }],
+            },
+        );
+        assert_eq!(
+            (
+                error.errors()[0].labels[0].start,
+                error.errors()[0].labels[0].end
+            ),
+            (1, 4)
+        );
+    }
+}
@@ -18,11 +18,13 @@ export function processTs(
 	sourceCode: string,
 	stripEnumAndNamespace?: boolean,
 	stripParametersWithModifiers?: boolean,
+	compilerOptionOverrides?: ts.CompilerOptions,
 ): {
 	ts: string;
 	js: string;
 	kind: "module" | "script";
 } | null {
+	const options = { ...compilerOptions, ...compilerOptionOverrides };
 	sourceCode = sourceCode.replaceAll("/*!", "/*").replaceAll('/// <reference', '//');
 	const project = createProjectSync({ useInMemoryFileSystem: true });
 	const sourceFile = project.createSourceFile(
@@ -34,7 +36,7 @@ export function processTs(
 	);
 	const program = project.createProgram({
 		rootNames: [TS_SOURCE_FILENAME],
-		options: compilerOptions,
+		options,
 	});
 	// With noCheck enabled, all preEmitDiagnostics are syntax errors
 	if (ts.getPreEmitDiagnostics(program).length > 0) {
@@ -101,7 +103,7 @@ export function processTs(
 		sourceCode = codeSegments.join('');
 	}
 
-	const { outputText } = ts.transpileModule(sourceCode, { compilerOptions });
+	const { outputText } = ts.transpileModule(sourceCode, { compilerOptions: options });
 
 	return {
 		ts: sourceCode,
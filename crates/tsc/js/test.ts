@@ -46,6 +46,15 @@ export const b = 'b';
         const result = processTs("class A { constructor(private a, b) {} }", true, true);
         assert.equal(result?.ts, "class A { constructor( a, b) {} }");
     })
+    it("should override compiler options", () => {
+        const result = processTs("class A { a = 1 }", false, false, { useDefineForClassFields: false });
+        assert.equal(result?.js, `class A {
+    constructor() {
+        this.a = 1;
+    }
+}
+`);
+    })
 });
 
 // describe("formatJs", () => {
@@ -73,6 +73,23 @@ impl Tsc {
         source: &str,
         strip_enum_and_namespace: bool,
         strip_parameters_with_modifiers: bool,
+    ) -> Option<TranspileOutput> {
+        self.process_ts_with_compiler_options(
+            source,
+            strip_enum_and_namespace,
+            strip_parameters_with_modifiers,
+            "{}",
+        )
+    }
+
+    /// Like [`process_ts`](Self::process_ts), with `compiler_option_overrides` (a JSON object like `{"experimentalDecorators":true}`)
+    /// merged into the default compiler options.
+    pub fn process_ts_with_compiler_options(
+        &mut self,
+        source: &str,
+        strip_enum_and_namespace: bool,
+        strip_parameters_with_modifiers: bool,
+        compiler_option_overrides: &str,
     ) -> Option<TranspileOutput> {
         with_isolate(|isolate| {
             let process_ts_func = self.process_ts_func.open(isolate);
@@ -84,6 +101,10 @@ impl Tsc {
             let strip_enum_and_namespace = v8::Boolean::new(handle_scope, strip_enum_and_namespace);
             let strip_parameters_with_modifiers =
                 v8::Boolean::new(handle_scope, strip_parameters_with_modifiers);
+            let compiler_option_overrides =
+                v8::String::new(handle_scope, compiler_option_overrides)?;
+            let compiler_option_overrides =
+                v8::json::parse(handle_scope, compiler_option_overrides)?;
 
             let result = process_ts_func.call(
                 handle_scope,
@@ -92,6 +113,7 @@ impl Tsc {
                     source.cast(),
                     strip_enum_and_namespace.cast(),
                     strip_parameters_with_modifiers.cast(),
+                    compiler_option_overrides,
                 ],
             )?;
             serde_v8::from_v8::<Option<TranspileOutput>>(handle_scope, result).ok()?
@@ -130,4 +152,17 @@ mod tests {
             SourceKind::Module
         );
     }
+    #[test]
+    fn compiler_option_overrides() {
+        let mut tsc = Tsc::new();
+        let output = tsc
+            .process_ts_with_compiler_options(
+                "export class A { a = 1 }",
+                false,
+                false,
+                r#"{"useDefineForClassFields":false}"#,
+            )
+            .unwrap();
+        assert!(output.js.contains("this.a = 1"));
+    }
 }
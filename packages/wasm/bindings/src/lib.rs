@@ -1,6 +1,6 @@
 use std::{cell::RefCell, fmt::Write};
 
-use oxidase::{oxc_diagnostics::NamedSource, SourceType};
+use oxidase::{oxc_diagnostics::NamedSource, SourceType, TranspileError, TranspileOptions};
 use wasm_bindgen::prelude::*;
 
 /*
@@ -21,20 +21,67 @@ pub fn transpile(mut source: String, path: Option<String>) -> Result<String, JsE
         SourceType::ts()
     };
     ALLOCATOR.with_borrow_mut(|allocator| {
-        let ret = oxidase::transpile(allocator, source_type, &mut source);
+        let ret = oxidase::try_transpile_in_place(
+            allocator,
+            source_type,
+            &mut source,
+            &TranspileOptions::default(),
+        );
         allocator.reset();
-        if ret.parser_panicked {
-            let mut error_msg = String::new();
-            for error in ret.parser_errors {
-                let error = if let Some(path) = &path {
-                    error.with_source_code(NamedSource::new(path, source.clone()))
-                } else {
-                    error.with_source_code(source.clone())
-                };
-                error_msg.write_fmt(format_args!("{:?}\n", error)).unwrap();
+        match ret {
+            Ok(_) => Ok(source),
+            // `source` is left untouched on errors
+            Err(err) => Err(to_js_error(&err, &path, Some(&source))),
+        }
+    })
+}
+
+fn to_js_error(err: &TranspileError, path: &Option<String>, source: Option<&str>) -> JsError {
+    let mut error_msg = String::new();
+    for error in err.errors() {
+        let error = error.to_diagnostic();
+        let error = match (path, source) {
+            (Some(path), Some(source)) => {
+                error.with_source_code(NamedSource::new(path, source.to_owned()))
             }
-            return Err(JsError::new(&error_msg));
+            (None, Some(source)) => error.with_source_code(source.to_owned()),
+            (_, None) => error.into(),
+        };
+        error_msg.write_fmt(format_args!("{:?}\n", error)).unwrap();
+    }
+    JsError::new(&error_msg)
+}
+
+/// Same as `transpile`, but takes UTF-16 code units (e.g. `Uint16Array`).
+///
+/// `source` is transpiled in place and `undefined` is returned if the output has the same length,
+/// which is always the case for sources with only erasable syntax.
+/// Otherwise `source` is left untouched and the output is returned as a new `Uint16Array`.
+/// `source` is still decoded to UTF-8 in WASM memory for parsing.
+#[wasm_bindgen(js_name = transpileUtf16)]
+pub fn transpile_utf16(
+    source: &mut [u16],
+    path: Option<String>,
+) -> Result<Option<Vec<u16>>, JsError> {
+    console_error_panic_hook::set_once();
+    let source_type = if let Some(path) = &path {
+        SourceType::from_path(path)?
+    } else {
+        SourceType::ts()
+    };
+    ALLOCATOR.with_borrow_mut(|allocator| {
+        let ret = oxidase::try_transpile_utf16_slice(
+            allocator,
+            source_type,
+            source,
+            &TranspileOptions::default(),
+        );
+        allocator.reset();
+        match ret {
+            Ok((_, output)) => Ok(output),
+            // Error spans are UTF-16 offsets, which can't be rendered on a UTF-8 source,
+            // so the errors are reported without the code
+            Err(err) => Err(to_js_error(&err, &path, None)),
         }
-        Ok(source)
     })
 }